@@ -0,0 +1,642 @@
+//! Socket-activated lazy start for FGP daemons.
+//!
+//! `fgp activator` listens on each installed service's `daemon.sock` and keeps
+//! it bound for as long as it runs. The first request starts the real daemon
+//! with `FGP_SOCKET` pointing at `daemon.backend.sock`, and every connection is
+//! proxied there. Because all traffic flows through the activator, concurrent
+//! callers share a single start, and daemons that see no requests for the idle
+//! timeout are stopped again. Daemons that ignore `FGP_SOCKET` bind
+//! `daemon.sock` themselves; their socket is moved aside once it is up.
+//!
+//! Health checks (e.g. from `fgp monitor`) and `stop` requests don't count as
+//! activity: while the daemon is down the activator answers them itself (with
+//! status `idle` and `stopped`), so polling or stopping never starts a daemon
+//! and polling doesn't keep it alive. Services managed by a
+//! systemd unit (`fgp service unit`) are left alone.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{installed_services, service, service_socket_path};
+
+/// How long to wait for a daemon to bind its socket after spawning it.
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait for a daemon to exit after asking it to stop.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+/// Poll interval for accept loops and readiness checks.
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+/// How often idle daemons and front sockets are checked.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+/// Environment variable telling a daemon which socket path to bind.
+pub const SOCKET_ENV: &str = "FGP_SOCKET";
+
+/// How the activator starts and stops a service's daemon.
+trait Launcher: Send + Sync {
+    /// Start the daemon, asking it to listen on `backend`.
+    fn start(&self, backend: &Path) -> Result<()>;
+    /// Ask the daemon listening on `backend` to exit.
+    fn stop(&self, backend: &Path) -> Result<()>;
+}
+
+/// Runs the entrypoint from the service's manifest.
+struct ManifestLauncher {
+    name: String,
+}
+
+impl Launcher for ManifestLauncher {
+    fn start(&self, backend: &Path) -> Result<()> {
+        let spec = service::spec_from_manifest(&self.name)?;
+        let mut child = Command::new(&spec.exec_start)
+            .current_dir(&spec.working_dir)
+            .env(SOCKET_ENV, backend)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", spec.exec_start.display()))?;
+        // Reap the daemon when it exits so it doesn't linger as a zombie
+        thread::spawn(move || {
+            let _ = child.wait();
+        });
+        Ok(())
+    }
+
+    fn stop(&self, backend: &Path) -> Result<()> {
+        let client = fgp_daemon::FgpClient::new(backend).context("Failed to connect to daemon")?;
+        // The connection usually drops as the daemon exits
+        let _ = client.stop();
+        Ok(())
+    }
+}
+
+/// Activation state for one service.
+struct Activated {
+    name: String,
+    /// `daemon.sock` - the path clients connect to, owned by the activator.
+    front: PathBuf,
+    /// `daemon.backend.sock` - where the running daemon's socket is moved.
+    backend: PathBuf,
+    /// Listener bound on `front`, plus the inode it was bound to.
+    listener: Mutex<(UnixListener, u64)>,
+    /// Serializes daemon start/stop so concurrent callers share one start.
+    lifecycle: Mutex<()>,
+    last_activity: Mutex<Instant>,
+    connections: AtomicUsize,
+    launcher: Box<dyn Launcher>,
+}
+
+/// Run the activator (`fgp activator`).
+///
+/// `services` limits activation to the named services (default: all installed).
+/// An `idle_timeout_secs` of 0 disables idle shutdown.
+pub fn run(services: &[String], idle_timeout_secs: u64) -> Result<()> {
+    let names = if services.is_empty() {
+        installed_services()?
+    } else {
+        services.to_vec()
+    };
+
+    if names.is_empty() {
+        bail!("No services installed. Run 'fgp install <package>' first.");
+    }
+
+    let idle_timeout = (idle_timeout_secs > 0).then(|| Duration::from_secs(idle_timeout_secs));
+
+    println!(
+        "{} Activator listening for {} service(s) (Ctrl+C to stop)...",
+        "→".blue().bold(),
+        names.len()
+    );
+    match idle_timeout {
+        Some(t) => println!("  Idle daemons stop after {}s", t.as_secs()),
+        None => println!("  Idle shutdown disabled"),
+    }
+    println!();
+
+    let mut handles = Vec::new();
+    for name in names {
+        let activated = match Activated::bind(&name) {
+            Ok(a) => Arc::new(a),
+            Err(e) => {
+                eprintln!("{} {}: {:#}", "✗".red().bold(), name, e);
+                continue;
+            }
+        };
+        println!("  {} {}", "●".green(), name);
+
+        let sweeper = Arc::clone(&activated);
+        thread::spawn(move || sweeper.sweep_loop(idle_timeout));
+        handles.push(thread::spawn(move || activated.accept_loop()));
+    }
+
+    if handles.is_empty() {
+        bail!("No service sockets could be bound");
+    }
+
+    for handle in handles {
+        let _ = handle.join();
+    }
+
+    Ok(())
+}
+
+impl Activated {
+    /// Take over a service's socket path.
+    fn bind(name: &str) -> Result<Self> {
+        // systemd would restart the daemon on `daemon.sock` behind our back
+        if service::has_unit(name) {
+            bail!(
+                "Managed by systemd unit {}; remove the unit to use the activator",
+                service::unit_name(name)
+            );
+        }
+
+        let front = service_socket_path(name);
+        let backend = front.with_file_name("daemon.backend.sock");
+        let launcher = Box::new(ManifestLauncher {
+            name: name.to_string(),
+        });
+        Self::new(name, front, backend, launcher)
+    }
+
+    /// Listen on `front` for a daemon that is started on `backend`.
+    ///
+    /// A daemon that is already running is adopted by moving its socket to the
+    /// backend path; a stale socket is removed.
+    fn new(
+        name: &str,
+        front: PathBuf,
+        backend: PathBuf,
+        launcher: Box<dyn Launcher>,
+    ) -> Result<Self> {
+        if front.exists() {
+            if UnixStream::connect(&front).is_ok() {
+                fs::rename(&front, &backend).context("Failed to adopt running daemon")?;
+            } else {
+                let _ = fs::remove_file(&front);
+            }
+        }
+
+        let listener = bind_listener(&front)?;
+
+        Ok(Self {
+            name: name.to_string(),
+            front,
+            backend,
+            listener: Mutex::new(listener),
+            lifecycle: Mutex::new(()),
+            last_activity: Mutex::new(Instant::now()),
+            connections: AtomicUsize::new(0),
+            launcher,
+        })
+    }
+
+    /// Accept client connections and proxy each one on its own thread.
+    fn accept_loop(self: Arc<Self>) {
+        loop {
+            let accepted = {
+                let guard = self.listener.lock().unwrap();
+                guard.0.accept()
+            };
+
+            match accepted {
+                Ok((stream, _)) => {
+                    let this = Arc::clone(&self);
+                    thread::spawn(move || {
+                        if let Err(e) = this.handle(stream) {
+                            eprintln!("{} {}: {:#}", "✗".red().bold(), this.name, e);
+                        }
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                Err(e) => {
+                    eprintln!("{} {}: accept failed: {}", "✗".red().bold(), self.name, e);
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        }
+    }
+
+    /// Proxy one client connection to the backend daemon, starting it if needed.
+    fn handle(&self, client: UnixStream) -> Result<()> {
+        // Accepted sockets may inherit non-blocking mode on some platforms
+        client.set_nonblocking(false)?;
+
+        let mut reader = BufReader::new(client.try_clone()?);
+        let mut line = String::new();
+
+        // Answer health checks and stops ourselves while the daemon is down,
+        // so they don't start it; the first real request does
+        let (mut backend, counts) = loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            if counts_as_activity(&line) {
+                self.touch();
+                break (self.ensure_backend()?, true);
+            }
+            match UnixStream::connect(&self.backend) {
+                Ok(stream) => break (stream, false),
+                Err(_) => {
+                    let mut writer = &client;
+                    writer.write_all(idle_response(&line).as_bytes())?;
+                }
+            }
+        };
+        backend.write_all(line.as_bytes())?;
+
+        self.connections.fetch_add(1, Ordering::SeqCst);
+
+        let mut backend_reader = backend.try_clone()?;
+        let mut client_writer = client.try_clone()?;
+        let downstream = thread::spawn(move || {
+            let _ = io::copy(&mut backend_reader, &mut client_writer);
+            let _ = client_writer.shutdown(Shutdown::Write);
+        });
+
+        let _ = self.pump(&mut reader, &mut backend, counts);
+        let _ = backend.shutdown(Shutdown::Write);
+        let _ = downstream.join();
+
+        self.connections.fetch_sub(1, Ordering::SeqCst);
+        if counts {
+            self.touch();
+        }
+
+        Ok(())
+    }
+
+    /// Copy client requests to the backend, recording activity per chunk.
+    fn pump(&self, from: &mut impl Read, to: &mut UnixStream, counts: bool) -> io::Result<()> {
+        let mut buf = [0u8; 8192];
+        loop {
+            let n = from.read(&mut buf)?;
+            if n == 0 {
+                return Ok(());
+            }
+            to.write_all(&buf[..n])?;
+            if counts {
+                self.touch();
+            }
+        }
+    }
+
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Connect to the backend daemon, starting it first if it isn't running.
+    fn ensure_backend(&self) -> Result<UnixStream> {
+        if let Ok(stream) = UnixStream::connect(&self.backend) {
+            return Ok(stream);
+        }
+
+        let _lifecycle = self.lifecycle.lock().unwrap();
+
+        // Another connection may have started it while we waited for the lock
+        if let Ok(stream) = UnixStream::connect(&self.backend) {
+            return Ok(stream);
+        }
+
+        self.start_daemon()?;
+        UnixStream::connect(&self.backend).context("Daemon started but backend is unreachable")
+    }
+
+    /// Start the daemon on the backend path and wait for it to come up.
+    ///
+    /// Must be called with the lifecycle lock held.
+    fn start_daemon(&self) -> Result<()> {
+        println!(
+            "[{}] {} Activating {}",
+            chrono::Local::now().format("%H:%M:%S"),
+            "→".blue().bold(),
+            self.name
+        );
+
+        let _ = fs::remove_file(&self.backend);
+        let result = self
+            .launcher
+            .start(&self.backend)
+            .and_then(|()| self.wait_for_daemon());
+
+        // A daemon that ignores FGP_SOCKET binds `daemon.sock` over our
+        // listener; move its socket aside and take the path back
+        if self.front_replaced() && UnixStream::connect(&self.front).is_ok() {
+            fs::rename(&self.front, &self.backend).context("Failed to move daemon socket")?;
+        }
+        self.rebind_front()?;
+
+        result
+    }
+
+    /// Wait until the daemon accepts connections on the backend path (or on
+    /// the front path, if it ignored FGP_SOCKET).
+    fn wait_for_daemon(&self) -> Result<()> {
+        let deadline = Instant::now() + START_TIMEOUT;
+        while Instant::now() < deadline {
+            if UnixStream::connect(&self.backend).is_ok()
+                || (self.front_replaced() && UnixStream::connect(&self.front).is_ok())
+            {
+                return Ok(());
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        bail!(
+            "Daemon did not bind {} within {}s",
+            self.backend.display(),
+            START_TIMEOUT.as_secs()
+        )
+    }
+
+    /// Whether `daemon.sock` is gone or no longer the socket we bound.
+    fn front_replaced(&self) -> bool {
+        let current_ino = fs::metadata(&self.front).map(|m| m.ino()).ok();
+        current_ino != Some(self.listener.lock().unwrap().1)
+    }
+
+    /// Replace the front listener if its socket file is gone or was replaced.
+    fn rebind_front(&self) -> Result<()> {
+        let mut guard = self.listener.lock().unwrap();
+        let current_ino = fs::metadata(&self.front).map(|m| m.ino()).ok();
+        if current_ino == Some(guard.1) {
+            return Ok(());
+        }
+
+        let _ = fs::remove_file(&self.front);
+        *guard = bind_listener(&self.front)?;
+        Ok(())
+    }
+
+    /// Periodically stop idle daemons and repair the front socket.
+    fn sweep_loop(self: Arc<Self>, idle_timeout: Option<Duration>) {
+        loop {
+            thread::sleep(SWEEP_INTERVAL);
+            self.sweep(idle_timeout);
+        }
+    }
+
+    /// Stop the daemon if it has been idle too long and repair the front socket.
+    fn sweep(&self, idle_timeout: Option<Duration>) {
+        if let Some(timeout) = idle_timeout {
+            let idle = self.last_activity.lock().unwrap().elapsed();
+            if idle >= timeout && self.connections.load(Ordering::SeqCst) == 0 {
+                if let Err(e) = self.stop_if_running() {
+                    eprintln!("{} {}: {:#}", "✗".red().bold(), self.name, e);
+                }
+            }
+        }
+
+        // `fgp start` may delete `daemon.sock` as stale; make sure clients can
+        // still reach us
+        let _lifecycle = self.lifecycle.lock().unwrap();
+        if let Err(e) = self.rebind_front() {
+            eprintln!("{} {}: {:#}", "✗".red().bold(), self.name, e);
+        }
+    }
+
+    /// Stop the backend daemon if it is running.
+    fn stop_if_running(&self) -> Result<()> {
+        let _lifecycle = self.lifecycle.lock().unwrap();
+
+        if UnixStream::connect(&self.backend).is_err() {
+            let _ = fs::remove_file(&self.backend);
+            return Ok(());
+        }
+
+        println!(
+            "[{}] {} Stopping idle {}",
+            chrono::Local::now().format("%H:%M:%S"),
+            "○".dimmed(),
+            self.name
+        );
+
+        self.launcher.stop(&self.backend)?;
+
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while Instant::now() < deadline && UnixStream::connect(&self.backend).is_ok() {
+            thread::sleep(POLL_INTERVAL);
+        }
+        let _ = fs::remove_file(&self.backend);
+
+        self.rebind_front()
+    }
+}
+
+/// Bind a non-blocking listener, returning it with its socket file's inode.
+fn bind_listener(path: &Path) -> Result<(UnixListener, u64)> {
+    let listener =
+        UnixListener::bind(path).with_context(|| format!("Failed to bind {}", path.display()))?;
+    listener.set_nonblocking(true)?;
+    let ino = fs::metadata(path)?.ino();
+    Ok((listener, ino))
+}
+
+/// Reply to a health check or stop on behalf of a stopped daemon.
+fn idle_response(request: &str) -> String {
+    let request = serde_json::from_str::<Value>(request).unwrap_or(Value::Null);
+    let status = match request["method"].as_str() {
+        Some("stop") => "stopped",
+        _ => "idle",
+    };
+    let response = json!({
+        "id": request["id"],
+        "ok": true,
+        "result": { "status": status },
+        "error": null,
+        "meta": { "server_ms": 0.0 }
+    });
+    format!("{}\n", response)
+}
+
+/// Whether a request counts towards keeping a daemon awake.
+///
+/// FGP requests are newline-delimited JSON; health checks and stops are
+/// excluded.
+fn counts_as_activity(first_line: &str) -> bool {
+    serde_json::from_str::<Value>(first_line)
+        .ok()
+        .and_then(|req| {
+            req["method"]
+                .as_str()
+                .map(|m| !matches!(m, "health" | "stop"))
+        })
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counts {
+        starts: AtomicUsize,
+        stops: AtomicUsize,
+    }
+
+    /// Starts an in-process daemon that answers each request with its method.
+    struct FakeLauncher {
+        counts: Arc<Counts>,
+        start_delay: Duration,
+    }
+
+    impl Launcher for FakeLauncher {
+        fn start(&self, backend: &Path) -> Result<()> {
+            self.counts.starts.fetch_add(1, Ordering::SeqCst);
+            let backend = backend.to_path_buf();
+            let delay = self.start_delay;
+            thread::spawn(move || {
+                thread::sleep(delay);
+                serve_fake_daemon(&backend);
+            });
+            Ok(())
+        }
+
+        fn stop(&self, backend: &Path) -> Result<()> {
+            self.counts.stops.fetch_add(1, Ordering::SeqCst);
+            request(backend, "stop");
+            Ok(())
+        }
+    }
+
+    fn serve_fake_daemon(path: &Path) {
+        let listener = UnixListener::bind(path).unwrap();
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            for line in BufReader::new(stream).lines().map_while(|l| l.ok()) {
+                let request: Value = serde_json::from_str(&line).unwrap();
+                let response = json!({
+                    "id": request["id"],
+                    "ok": true,
+                    "result": { "method": request["method"] }
+                });
+                writeln!(writer, "{}", response).unwrap();
+                if request["method"] == "stop" {
+                    let _ = fs::remove_file(path);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Send one request and return the response.
+    fn request(socket: &Path, method: &str) -> Value {
+        let mut stream = UnixStream::connect(socket).unwrap();
+        writeln!(stream, "{}", json!({ "id": "1", "method": method })).unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn activator(dir: &Path, start_delay: Duration) -> (Arc<Activated>, Arc<Counts>) {
+        let counts = Arc::new(Counts::default());
+        let launcher = FakeLauncher {
+            counts: Arc::clone(&counts),
+            start_delay,
+        };
+        let activated = Arc::new(
+            Activated::new(
+                "fake",
+                dir.join("daemon.sock"),
+                dir.join("daemon.backend.sock"),
+                Box::new(launcher),
+            )
+            .unwrap(),
+        );
+        let acceptor = Arc::clone(&activated);
+        thread::spawn(move || acceptor.accept_loop());
+        (activated, counts)
+    }
+
+    #[test]
+    fn test_health_is_answered_while_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (activated, counts) = activator(dir.path(), Duration::ZERO);
+
+        let response = request(&activated.front, "health");
+        assert_eq!(response["id"], "1");
+        assert_eq!(response["ok"], true);
+        assert_eq!(response["result"]["status"], "idle");
+        assert_eq!(counts.starts.load(Ordering::SeqCst), 0);
+        assert!(!activated.backend.exists());
+    }
+
+    #[test]
+    fn test_stop_is_answered_while_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (activated, counts) = activator(dir.path(), Duration::ZERO);
+
+        let response = request(&activated.front, "stop");
+        assert_eq!(response["id"], "1");
+        assert_eq!(response["ok"], true);
+        assert_eq!(response["result"]["status"], "stopped");
+        assert_eq!(counts.starts.load(Ordering::SeqCst), 0);
+        assert!(!activated.backend.exists());
+    }
+
+    #[test]
+    fn test_requests_start_daemon_once_and_are_proxied() {
+        let dir = tempfile::tempdir().unwrap();
+        let (activated, counts) = activator(dir.path(), Duration::from_millis(300));
+        let front_ino = fs::metadata(&activated.front).unwrap().ino();
+
+        let clients: Vec<_> = (0..4)
+            .map(|_| {
+                let front = activated.front.clone();
+                thread::spawn(move || request(&front, "fake.echo"))
+            })
+            .collect();
+
+        // The front socket stays connectable while the daemon is starting
+        thread::sleep(Duration::from_millis(100));
+        assert!(UnixStream::connect(&activated.front).is_ok());
+
+        for client in clients {
+            assert_eq!(client.join().unwrap()["result"]["method"], "fake.echo");
+        }
+        assert_eq!(counts.starts.load(Ordering::SeqCst), 1);
+        assert_eq!(fs::metadata(&activated.front).unwrap().ino(), front_ino);
+
+        // Once the daemon runs, health checks reach it
+        let response = request(&activated.front, "health");
+        assert_eq!(response["result"]["method"], "health");
+    }
+
+    #[test]
+    fn test_idle_daemon_is_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (activated, counts) = activator(dir.path(), Duration::ZERO);
+
+        request(&activated.front, "fake.echo");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while activated.connections.load(Ordering::SeqCst) > 0 && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        activated.sweep(Some(Duration::from_secs(60)));
+        assert_eq!(counts.stops.load(Ordering::SeqCst), 0);
+
+        activated.sweep(Some(Duration::ZERO));
+        assert_eq!(counts.stops.load(Ordering::SeqCst), 1);
+        assert!(!activated.backend.exists());
+        assert_eq!(
+            request(&activated.front, "health")["result"]["status"],
+            "idle"
+        );
+
+        request(&activated.front, "fake.echo");
+        assert_eq!(counts.starts.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,490 @@
+//! Diagnose common problems with the local FGP installation.
+//!
+//! Each finding comes with a suggested fix. `fgp doctor --fix` applies the
+//! safe ones (removing stale sockets and dangling links, marking entrypoints
+//! executable, dropping missing taps); the rest need a human.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::io::ErrorKind;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+use super::skill::{self, InstalledSkills};
+use super::skill_tap;
+use super::{fgp_services_dir, service};
+
+/// Agent config files FGP writes MCP entries into, with their format.
+const AGENT_CONFIG_FILES: &[(&str, &str, ConfigFormat)] = &[
+    ("Claude Code", "~/.claude.json", ConfigFormat::Json),
+    ("Cursor", "~/.cursor/mcp.json", ConfigFormat::Json),
+    (
+        "Windsurf",
+        "~/.codeium/windsurf/mcp_config.json",
+        ConfigFormat::Json,
+    ),
+    ("Gemini CLI", "~/.gemini/settings.json", ConfigFormat::Json),
+    ("Continue", "~/.continue/config.yaml", ConfigFormat::Yaml),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum ConfigFormat {
+    Json,
+    Yaml,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Warning,
+    Error,
+}
+
+/// A fix `--fix` may apply without asking.
+#[derive(Debug)]
+enum Fix {
+    /// Delete a stale socket or dangling symlink
+    Remove(PathBuf),
+    /// Add execute permission to an entrypoint
+    MakeExecutable(PathBuf),
+    /// Drop a tap whose clone is gone from taps.json
+    ForgetTap(String),
+}
+
+impl Fix {
+    fn apply(&self) -> Result<String> {
+        match self {
+            Fix::Remove(path) => {
+                fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+                Ok(format!("Removed {}", path.display()))
+            }
+            Fix::MakeExecutable(path) => {
+                let mut perms = fs::metadata(path)?.permissions();
+                // Add execute only for those who can already read the file
+                let mode = perms.mode();
+                perms.set_mode(mode | ((mode & 0o444) >> 2));
+                fs::set_permissions(path, perms)
+                    .with_context(|| format!("Failed to chmod {}", path.display()))?;
+                Ok(format!("Made {} executable", path.display()))
+            }
+            Fix::ForgetTap(name) => {
+                let mut config = skill_tap::load_taps_config()?;
+                config.taps.remove(name);
+                skill_tap::save_taps_config(&config)?;
+                Ok(format!("Removed tap '{}' from taps.json", name))
+            }
+        }
+    }
+}
+
+/// A problem found by a check.
+#[derive(Debug)]
+struct Finding {
+    severity: Severity,
+    problem: String,
+    suggestion: String,
+    fix: Option<Fix>,
+}
+
+impl Finding {
+    fn error(problem: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            problem: problem.into(),
+            suggestion: suggestion.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(problem: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(problem, suggestion)
+        }
+    }
+
+    fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// A group of related checks.
+type Check = fn() -> Vec<Finding>;
+
+/// Run all checks, optionally applying safe fixes.
+pub fn run(fix: bool) -> Result<()> {
+    println!("{}", "Checking FGP environment...".bold());
+    println!();
+
+    let sections: [(&str, Check); 5] = [
+        ("Services", check_services),
+        ("Skills", check_skills),
+        ("Taps", check_taps),
+        ("Agent configs", check_agent_configs),
+        ("Credentials", check_credentials),
+    ];
+
+    let mut remaining = 0;
+    let mut fixable = 0;
+
+    for (name, check) in sections {
+        let findings = check();
+        if findings.is_empty() {
+            println!("  {} {}", "✓".green().bold(), name);
+            continue;
+        }
+
+        println!("  {} {}", "✗".red().bold(), name.bold());
+        for finding in findings {
+            let icon = match finding.severity {
+                Severity::Error => "✗".red().bold(),
+                Severity::Warning => "!".yellow().bold(),
+            };
+            println!("    {} {}", icon, finding.problem);
+
+            match (&finding.fix, fix) {
+                (Some(action), true) => match action.apply() {
+                    Ok(done) => {
+                        println!("      {} {}", "✓".green().bold(), done);
+                        continue;
+                    }
+                    Err(e) => println!("      {} Fix failed: {}", "✗".red().bold(), e),
+                },
+                (Some(_), false) => fixable += 1,
+                (None, _) => {}
+            }
+
+            println!("      {} {}", "fix:".dimmed(), finding.suggestion);
+            remaining += 1;
+        }
+    }
+
+    println!();
+    if remaining == 0 {
+        println!("{} No problems found", "✓".green().bold());
+        return Ok(());
+    }
+
+    if fixable > 0 {
+        println!(
+            "{} {} can be fixed automatically with {}",
+            "→".blue().bold(),
+            if fixable == 1 {
+                "1 problem".to_string()
+            } else {
+                format!("{} problems", fixable)
+            },
+            "fgp doctor --fix".cyan()
+        );
+    }
+
+    bail!("{} problem(s) found", remaining)
+}
+
+/// Check installed services: manifests, entrypoints and sockets.
+fn check_services() -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let services_dir = fgp_services_dir();
+    let Ok(entries) = fs::read_dir(&services_dir) else {
+        return findings;
+    };
+
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        findings.extend(check_manifest(&name, &dir));
+
+        let socket = dir.join("daemon.sock");
+        if is_stale_socket(&socket) {
+            findings.push(
+                Finding::warning(
+                    format!("{}: stale socket at {}", name, socket.display()),
+                    "Remove the socket; the daemon isn't listening on it",
+                )
+                .with_fix(Fix::Remove(socket)),
+            );
+        }
+    }
+
+    findings
+}
+
+/// Check a service's manifest and the entrypoint it declares.
+fn check_manifest(name: &str, dir: &Path) -> Option<Finding> {
+    let manifest_path = dir.join("manifest.json");
+    let reinstall = format!("Reinstall the package with 'fgp install <path>' ({})", name);
+
+    let content = match fs::read_to_string(&manifest_path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Some(Finding::error(
+                format!("{}: no manifest.json", name),
+                reinstall,
+            ));
+        }
+        Err(e) => {
+            return Some(Finding::error(
+                format!("{}: manifest.json unreadable: {}", name, e),
+                reinstall,
+            ));
+        }
+    };
+
+    let manifest: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(m) => m,
+        Err(e) => {
+            return Some(Finding::error(
+                format!("{}: manifest.json is not valid JSON: {}", name, e),
+                reinstall,
+            ));
+        }
+    };
+
+    let Some(entrypoint) = manifest["daemon"]["entrypoint"].as_str() else {
+        return Some(Finding::error(
+            format!("{}: manifest.json has no daemon.entrypoint", name),
+            reinstall,
+        ));
+    };
+
+    let path = service::resolve(dir, entrypoint);
+    match fs::metadata(&path) {
+        Err(_) => Some(Finding::error(
+            format!("{}: entrypoint {} is missing", name, path.display()),
+            reinstall,
+        )),
+        Ok(meta) if meta.permissions().mode() & 0o111 == 0 => Some(
+            Finding::error(
+                format!("{}: entrypoint {} is not executable", name, path.display()),
+                format!("chmod +x {}", path.display()),
+            )
+            .with_fix(Fix::MakeExecutable(path)),
+        ),
+        Ok(_) => None,
+    }
+}
+
+/// Whether a socket file exists that nothing is listening on.
+fn is_stale_socket(path: &Path) -> bool {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => matches!(
+            UnixStream::connect(path),
+            Err(e) if e.kind() == ErrorKind::ConnectionRefused || e.kind() == ErrorKind::NotFound
+        ),
+        _ => false,
+    }
+}
+
+/// Check installed skills: the tracking file and source links.
+fn check_skills() -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let installed_path = skill::installed_skills_path();
+    if let Ok(content) = fs::read_to_string(&installed_path) {
+        if let Err(e) = serde_json::from_str::<InstalledSkills>(&content) {
+            findings.push(Finding::error(
+                format!("{} is corrupt: {}", installed_path.display(), e),
+                format!(
+                    "Move it aside (mv {0} {0}.bak) and reinstall your skills",
+                    installed_path.display()
+                ),
+            ));
+        }
+    }
+
+    let installed_dir = skill::skills_dir().join("installed");
+    let Ok(entries) = fs::read_dir(&installed_dir) else {
+        return findings;
+    };
+
+    let mut links: Vec<PathBuf> = entries.flatten().map(|e| e.path().join("source")).collect();
+    links.sort();
+
+    for link in links {
+        let Ok(target) = fs::read_link(&link) else {
+            continue;
+        };
+        if !link.exists() {
+            let skill_name = link
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            findings.push(
+                Finding::error(
+                    format!(
+                        "skill '{}': source link points to missing {}",
+                        skill_name,
+                        target.display()
+                    ),
+                    format!(
+                        "Remove the dangling link, then reinstall with 'fgp skill install {}'",
+                        skill_name
+                    ),
+                )
+                .with_fix(Fix::Remove(link)),
+            );
+        }
+    }
+
+    findings
+}
+
+/// Check that every tap in taps.json still has its clone.
+fn check_taps() -> Vec<Finding> {
+    let config_path = skill_tap::taps_config_path();
+    if !config_path.exists() {
+        return Vec::new();
+    }
+
+    let config = match skill_tap::load_taps_config() {
+        Ok(c) => c,
+        Err(e) => {
+            return vec![Finding::error(
+                format!("{} is corrupt: {}", config_path.display(), e),
+                format!(
+                    "Move it aside (mv {0} {0}.bak) and re-add your taps",
+                    config_path.display()
+                ),
+            )];
+        }
+    };
+
+    let mut taps: Vec<_> = config.taps.iter().collect();
+    taps.sort_by(|a, b| a.0.cmp(b.0));
+
+    taps.into_iter()
+        .filter(|(_, tap)| !Path::new(&tap.path).is_dir())
+        .map(|(name, tap)| {
+            Finding::warning(
+                format!("tap '{}': directory {} is missing", name, tap.path),
+                format!(
+                    "Forget the tap, or re-clone it with 'fgp skill tap add {}'",
+                    tap.repo
+                ),
+            )
+            .with_fix(Fix::ForgetTap(name.clone()))
+        })
+        .collect()
+}
+
+/// Check that agent config files FGP edits still parse.
+fn check_agent_configs() -> Vec<Finding> {
+    AGENT_CONFIG_FILES
+        .iter()
+        .filter_map(|(agent, path, format)| {
+            let expanded = shellexpand::tilde(path);
+            let content = fs::read_to_string(expanded.as_ref()).ok()?;
+
+            let error = match format {
+                ConfigFormat::Json => serde_json::from_str::<serde_json::Value>(&content)
+                    .err()
+                    .map(|e| e.to_string()),
+                ConfigFormat::Yaml => serde_yaml::from_str::<serde_yaml::Value>(&content)
+                    .err()
+                    .map(|e| e.to_string()),
+            }?;
+
+            Some(Finding::error(
+                format!("{} config {} doesn't parse: {}", agent, path, error),
+                format!(
+                    "Fix the syntax error by hand; {} ignores it until then",
+                    agent
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Check that OAuth credentials declared in service manifests exist.
+fn check_credentials() -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let Ok(entries) = fs::read_dir(fgp_services_dir()) else {
+        return findings;
+    };
+
+    let mut manifests: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path().join("manifest.json"))
+        .collect();
+    manifests.sort();
+
+    for manifest_path in manifests {
+        let Some(manifest) = fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        else {
+            continue;
+        };
+        let name = manifest["name"].as_str().unwrap_or_default();
+        let auth = &manifest["auth"];
+
+        let Some(credentials) = auth["credentials_path"].as_str().filter(|p| !p.is_empty()) else {
+            continue;
+        };
+        let expanded = shellexpand::tilde(credentials);
+        if Path::new(expanded.as_ref()).exists() {
+            continue;
+        }
+
+        let provider = auth["provider"].as_str().unwrap_or("OAuth");
+        findings.push(Finding::warning(
+            format!(
+                "{}: {} credentials missing at {}",
+                name, provider, credentials
+            ),
+            format!(
+                "Download OAuth client credentials from {} and save them to {}",
+                provider, credentials
+            ),
+        ));
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_detects_stale_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+
+        let listener = UnixListener::bind(&socket).unwrap();
+        assert!(!is_stale_socket(&socket));
+
+        drop(listener);
+        assert!(is_stale_socket(&socket));
+        assert!(!is_stale_socket(&dir.path().join("missing.sock")));
+    }
+
+    #[test]
+    fn test_make_executable_keeps_access_narrow() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("daemon");
+        fs::write(&binary, "").unwrap();
+
+        for (before, after) in [(0o600, 0o700), (0o640, 0o750), (0o644, 0o755)] {
+            fs::set_permissions(&binary, fs::Permissions::from_mode(before)).unwrap();
+            Fix::MakeExecutable(binary.clone()).apply().unwrap();
+            let mode = fs::metadata(&binary).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode, after, "{:o}", before);
+        }
+    }
+}
@@ -5,7 +5,11 @@ use colored::Colorize;
 
 use super::service_socket_path;
 
-pub fn run(service: &str) -> Result<()> {
+pub fn run(service: &str, history: bool, since: &str) -> Result<()> {
+    if history {
+        return super::health_history::show(service, since);
+    }
+
     let socket_path = service_socket_path(service);
 
     if !socket_path.exists() {
@@ -34,6 +38,7 @@ pub fn run(service: &str) -> Result<()> {
             "healthy" => "●".green(),
             "degraded" => "◐".yellow(),
             "unhealthy" => "○".red(),
+            "idle" => "○".dimmed(),
             _ => "?".dimmed(),
         };
 
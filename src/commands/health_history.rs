@@ -0,0 +1,319 @@
+//! Persistent health history for FGP daemons.
+//!
+//! The health monitor appends one probe per service per poll to
+//! `~/.fgp/health/<service>.jsonl`. `fgp health <service> --history`
+//! summarizes that history: availability, latency percentiles and incidents.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use super::parse_duration;
+
+/// How long probes are kept before being pruned.
+pub const HISTORY_RETENTION_DAYS: i64 = 30;
+
+/// A single recorded health probe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthProbe {
+    pub timestamp: DateTime<Utc>,
+    /// Daemon-reported status ("healthy", "degraded", ...) or "stopped"/"error".
+    pub status: String,
+    /// Round-trip latency of the health call, if the daemon answered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    /// Sub-service name → ok.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub services: BTreeMap<String, bool>,
+}
+
+impl HealthProbe {
+    /// Whether this probe counts towards availability.
+    pub fn is_up(&self) -> bool {
+        matches!(self.status.as_str(), "healthy" | "running" | "idle" | "degraded")
+    }
+}
+
+/// A contiguous run of failed probes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Incident {
+    pub start: DateTime<Utc>,
+    /// Timestamp of the first healthy probe afterwards, `None` if still ongoing.
+    pub end: Option<DateTime<Utc>>,
+    pub status: String,
+    pub probes: usize,
+}
+
+/// Get the health history directory.
+fn history_dir() -> PathBuf {
+    let base = shellexpand::tilde("~/.fgp/health");
+    PathBuf::from(base.as_ref())
+}
+
+/// Get the history file for a service.
+fn history_path(service: &str) -> PathBuf {
+    history_dir().join(format!("{}.jsonl", service))
+}
+
+/// Append a probe to a service's history.
+pub fn record(service: &str, probe: &HealthProbe) -> Result<()> {
+    let dir = history_dir();
+    fs::create_dir_all(&dir).context("Failed to create health history directory")?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_path(service))
+        .context("Failed to open health history")?;
+
+    writeln!(file, "{}", serde_json::to_string(probe)?)?;
+    Ok(())
+}
+
+/// Load probes for a service recorded at or after `since`.
+///
+/// Malformed lines (e.g. a partially written last line) are skipped.
+pub fn load(service: &str, since: DateTime<Utc>) -> Result<Vec<HealthProbe>> {
+    let path = history_path(service);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = File::open(&path).context("Failed to open health history")?;
+    let probes = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<HealthProbe>(&line).ok())
+        .filter(|p| p.timestamp >= since)
+        .collect();
+
+    Ok(probes)
+}
+
+/// Drop probes older than the retention period from every history file.
+pub fn prune_all() -> Result<()> {
+    let dir = history_dir();
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    let cutoff = Utc::now() - chrono::Duration::days(HISTORY_RETENTION_DAYS);
+
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Some(service) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        let kept = load(service, cutoff)?;
+        let tmp = path.with_extension("jsonl.tmp");
+        {
+            let mut file = File::create(&tmp)?;
+            for probe in &kept {
+                writeln!(file, "{}", serde_json::to_string(probe)?)?;
+            }
+        }
+        fs::rename(&tmp, &path)?;
+    }
+
+    Ok(())
+}
+
+/// Percentage of probes that were up.
+pub fn availability(probes: &[HealthProbe]) -> Option<f64> {
+    if probes.is_empty() {
+        return None;
+    }
+    let up = probes.iter().filter(|p| p.is_up()).count();
+    Some(up as f64 * 100.0 / probes.len() as f64)
+}
+
+/// Nearest-rank percentile of a sorted slice.
+pub fn percentile(sorted: &[f64], pct: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Group consecutive failed probes into incidents.
+pub fn incidents(probes: &[HealthProbe]) -> Vec<Incident> {
+    let mut incidents = Vec::new();
+    let mut current: Option<Incident> = None;
+
+    for probe in probes {
+        if probe.is_up() {
+            if let Some(mut incident) = current.take() {
+                incident.end = Some(probe.timestamp);
+                incidents.push(incident);
+            }
+        } else if let Some(incident) = current.as_mut() {
+            incident.probes += 1;
+        } else {
+            current = Some(Incident {
+                start: probe.timestamp,
+                end: None,
+                status: probe.status.clone(),
+                probes: 1,
+            });
+        }
+    }
+
+    incidents.extend(current);
+    incidents
+}
+
+/// Show a service's health history (`fgp health <service> --history`).
+pub fn show(service: &str, since: &str) -> Result<()> {
+    let window = parse_duration(since)?;
+    let since_ts = Utc::now() - window;
+    let probes = load(service, since_ts)?;
+
+    if probes.is_empty() {
+        bail!(
+            "No health history for '{}' in the last {}. History is recorded by 'fgp monitor'.",
+            service,
+            since
+        );
+    }
+
+    let mut latencies: Vec<f64> = probes.iter().filter_map(|p| p.latency_ms).collect();
+    latencies.sort_by(|a, b| a.total_cmp(b));
+
+    println!(
+        "{} {} health history (last {})",
+        "→".blue().bold(),
+        service.bold(),
+        since
+    );
+    println!();
+    println!("  Probes:        {}", probes.len());
+
+    if let Some(pct) = availability(&probes) {
+        let pct_str = format!("{:.2}%", pct);
+        let colored_pct = if pct >= 99.9 {
+            pct_str.green()
+        } else if pct >= 99.0 {
+            pct_str.yellow()
+        } else {
+            pct_str.red()
+        };
+        println!("  Availability:  {}", colored_pct);
+    }
+
+    if !latencies.is_empty() {
+        let fmt = |p: f64| {
+            percentile(&latencies, p)
+                .map(|v| format!("{:.1}ms", v))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        println!(
+            "  Latency:       p50 {}  p95 {}  p99 {}  max {}",
+            fmt(50.0),
+            fmt(95.0),
+            fmt(99.0),
+            fmt(100.0)
+        );
+    }
+
+    let incidents = incidents(&probes);
+    println!();
+    if incidents.is_empty() {
+        println!("  {} No incidents", "✓".green());
+        return Ok(());
+    }
+
+    println!("  Incidents ({}):", incidents.len());
+    for incident in &incidents {
+        let start = incident.start.with_timezone(&chrono::Local);
+        match incident.end {
+            Some(end) => {
+                let duration = (end - incident.start).num_seconds().max(0) as u64;
+                println!(
+                    "    {} {} {} for {} ({} probes)",
+                    "✗".red(),
+                    start.format("%Y-%m-%d %H:%M:%S"),
+                    incident.status,
+                    format_duration_secs(duration),
+                    incident.probes
+                );
+            }
+            None => {
+                println!(
+                    "    {} {} {} (ongoing, {} probes)",
+                    "✗".red(),
+                    start.format("%Y-%m-%d %H:%M:%S"),
+                    incident.status,
+                    incident.probes
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Format a duration in seconds as a compact string.
+fn format_duration_secs(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else if secs < 86400 {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    } else {
+        format!("{}d {}h", secs / 86400, (secs % 86400) / 3600)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(offset_secs: i64, status: &str, latency: Option<f64>) -> HealthProbe {
+        HealthProbe {
+            timestamp: DateTime::from_timestamp(1_700_000_000 + offset_secs, 0).unwrap(),
+            status: status.to_string(),
+            latency_ms: latency,
+            services: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        assert_eq!(percentile(&sorted, 50.0), Some(5.0));
+        assert_eq!(percentile(&sorted, 95.0), Some(10.0));
+        assert_eq!(percentile(&sorted, 0.0), Some(1.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn test_incidents_and_availability() {
+        let probes = vec![
+            probe(0, "healthy", Some(1.0)),
+            probe(60, "error", None),
+            probe(120, "stopped", None),
+            probe(180, "degraded", Some(2.0)),
+            probe(240, "unhealthy", Some(3.0)),
+        ];
+
+        let incidents = incidents(&probes);
+        assert_eq!(incidents.len(), 2);
+        assert_eq!(incidents[0].status, "error");
+        assert_eq!(incidents[0].probes, 2);
+        assert_eq!(incidents[0].end, Some(probes[3].timestamp));
+        assert_eq!(incidents[1].end, None);
+
+        assert_eq!(availability(&probes), Some(40.0));
+    }
+}
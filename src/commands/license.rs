@@ -88,7 +88,7 @@ fn license_cache_path() -> PathBuf {
 }
 
 /// Load license cache
-fn load_license_cache() -> Result<LicenseCache> {
+pub fn load_license_cache() -> Result<LicenseCache> {
     let path = license_cache_path();
     if !path.exists() {
         return Ok(LicenseCache::default());
@@ -0,0 +1,289 @@
+//! Parse daemon log lines into structured records.
+//!
+//! Understands JSON lines (e.g. `tracing-subscriber` JSON output), the default
+//! `tracing` fmt layout and `env_logger`'s bracketed layout. Anything else is
+//! kept as an unstructured message.
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Log severity, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "TRACE" => Ok(Level::Trace),
+            "DEBUG" => Ok(Level::Debug),
+            "INFO" => Ok(Level::Info),
+            "WARN" | "WARNING" => Ok(Level::Warn),
+            "ERROR" | "ERR" | "FATAL" | "CRITICAL" => Ok(Level::Error),
+            other => Err(format!(
+                "Unknown log level '{}'. Use trace, debug, info, warn or error",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        };
+        f.pad(s)
+    }
+}
+
+/// Which layout a line was parsed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Json,
+    Tracing,
+    EnvLogger,
+    Plain,
+}
+
+/// A parsed log line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<Level>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub message: String,
+    #[serde(skip)]
+    pub format: LogFormat,
+    #[serde(skip)]
+    pub raw: String,
+}
+
+impl LogRecord {
+    fn with_raw(mut self, raw: &str) -> Self {
+        self.raw = raw.to_string();
+        self
+    }
+
+    /// Whether this line is a continuation of the previous record
+    /// (e.g. a backtrace), rather than a record of its own.
+    pub fn is_continuation(&self) -> bool {
+        self.format == LogFormat::Plain
+    }
+}
+
+/// Parse a single log line.
+pub fn parse_line(line: &str) -> LogRecord {
+    let clean = strip_ansi(line);
+    let trimmed = clean.trim_end();
+
+    parse_json(trimmed)
+        .or_else(|| parse_env_logger(trimmed))
+        .or_else(|| parse_tracing(trimmed))
+        .unwrap_or_else(|| LogRecord {
+            timestamp: None,
+            level: None,
+            target: None,
+            message: trimmed.to_string(),
+            format: LogFormat::Plain,
+            raw: String::new(),
+        })
+        .with_raw(line)
+}
+
+/// Parse a JSON log line.
+fn parse_json(line: &str) -> Option<LogRecord> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let obj = value.as_object()?;
+
+    let str_field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+            .map(String::from)
+    };
+
+    let timestamp =
+        str_field(&["timestamp", "time", "ts", "@timestamp"]).and_then(|t| parse_timestamp(&t));
+    let level = str_field(&["level", "lvl", "severity"]).and_then(|l| l.parse().ok());
+    let target = str_field(&["target", "logger", "module", "name"]);
+
+    // tracing-subscriber nests the message under "fields"
+    let message = str_field(&["message", "msg"])
+        .or_else(|| {
+            obj.get("fields")
+                .and_then(|f| f.get("message"))
+                .and_then(|m| m.as_str())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| line.to_string());
+
+    Some(LogRecord {
+        timestamp,
+        level,
+        target,
+        message,
+        format: LogFormat::Json,
+        raw: String::new(),
+    })
+}
+
+/// Parse env_logger's `[2024-01-15T10:30:00Z INFO  my_crate::module] message`.
+fn parse_env_logger(line: &str) -> Option<LogRecord> {
+    let rest = line.strip_prefix('[')?;
+    let close = rest.find(']')?;
+    let (header, message) = (&rest[..close], rest[close + 1..].trim_start());
+
+    let mut parts = header.split_whitespace();
+    let first = parts.next()?;
+
+    // The timestamp is optional in env_logger output
+    let (timestamp, level) = match first.parse::<Level>() {
+        Ok(level) => (None, level),
+        Err(_) => (
+            Some(parse_timestamp(first)?),
+            parts.next()?.parse::<Level>().ok()?,
+        ),
+    };
+    let target = parts.next().map(String::from);
+
+    Some(LogRecord {
+        timestamp,
+        level: Some(level),
+        target,
+        message: message.to_string(),
+        format: LogFormat::EnvLogger,
+        raw: String::new(),
+    })
+}
+
+/// Parse tracing fmt's `2024-01-15T10:30:00.123Z  INFO fgp_daemon::server: message`.
+fn parse_tracing(line: &str) -> Option<LogRecord> {
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let ts_str = parts.next()?;
+    let rest = parts.next()?.trim_start();
+
+    let timestamp = parse_timestamp(ts_str)?;
+
+    let mut parts = rest.splitn(2, char::is_whitespace);
+    let level: Level = parts.next()?.parse().ok()?;
+    let rest = parts.next().unwrap_or("").trim_start();
+
+    // "target: message" - the target is a path without spaces ending in ':'
+    let (target, message) = match rest.split_once(": ") {
+        Some((t, m)) if !t.is_empty() && !t.contains(char::is_whitespace) => {
+            (Some(t.to_string()), m.to_string())
+        }
+        _ => (None, rest.to_string()),
+    };
+
+    Some(LogRecord {
+        timestamp: Some(timestamp),
+        level: Some(level),
+        target,
+        message,
+        format: LogFormat::Tracing,
+        raw: String::new(),
+    })
+}
+
+/// Parse an RFC 3339 timestamp, or a naive local `YYYY-MM-DD[ T]HH:MM:SS[.f]`.
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Some(ts.with_timezone(&Utc));
+    }
+
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .and_then(|naive| Local.from_local_datetime(&naive).single())
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+/// Remove ANSI escape sequences (tracing colours its output when attached to a TTY).
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Skip parameters until the final byte (an ASCII letter)
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tracing_line() {
+        let record =
+            parse_line("2024-01-15T10:30:00.123456Z  WARN fgp_daemon::server: slow request");
+        assert_eq!(record.format, LogFormat::Tracing);
+        assert_eq!(record.level, Some(Level::Warn));
+        assert_eq!(record.target.as_deref(), Some("fgp_daemon::server"));
+        assert_eq!(record.message, "slow request");
+        assert!(record.timestamp.is_some());
+    }
+
+    #[test]
+    fn test_parse_env_logger_line() {
+        let record = parse_line("[2024-01-15T10:30:00Z ERROR my_crate::db] connection lost");
+        assert_eq!(record.format, LogFormat::EnvLogger);
+        assert_eq!(record.level, Some(Level::Error));
+        assert_eq!(record.target.as_deref(), Some("my_crate::db"));
+        assert_eq!(record.message, "connection lost");
+    }
+
+    #[test]
+    fn test_parse_json_line() {
+        let record = parse_line(
+            r#"{"timestamp":"2024-01-15T10:30:00Z","level":"INFO","target":"fgp_gmail","fields":{"message":"fetched 10 messages"}}"#,
+        );
+        assert_eq!(record.format, LogFormat::Json);
+        assert_eq!(record.level, Some(Level::Info));
+        assert_eq!(record.message, "fetched 10 messages");
+    }
+
+    #[test]
+    fn test_message_mentioning_error_is_not_an_error() {
+        let record = parse_line(
+            "\x1b[2m2024-01-15T10:30:00Z\x1b[0m \x1b[32m INFO\x1b[0m fgp_gmail: retrying after error",
+        );
+        assert_eq!(record.level, Some(Level::Info));
+
+        let plain = parse_line("thread 'main' panicked: ERROR in handler");
+        assert_eq!(plain.level, None);
+        assert!(plain.is_continuation());
+    }
+}
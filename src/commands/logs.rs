@@ -1,13 +1,173 @@
 //! View daemon logs in the terminal.
+//!
+//! Lines are parsed into structured records (see `log_parser`) so they can be
+//! filtered by level, time range and message, and printed as JSON.
+//! Several services can be shown at once, interleaved by timestamp.
 
 use anyhow::{bail, Context, Result};
-use colored::Colorize;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
-use std::path::PathBuf;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use colored::{Color, Colorize};
+use flate2::read::GzDecoder;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::Duration;
 
+use super::log_parser::{self, Level, LogFormat, LogRecord};
+use super::{installed_services, parse_duration};
+
+/// How often `--follow` checks for new data.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Block size for reading logs backwards.
+const TAIL_BLOCK_SIZE: u64 = 64 * 1024;
+
+/// Most lines read from one log per poll when following several services,
+/// so a chatty daemon can't starve the others.
+const FOLLOW_BATCH_LINES: usize = 1000;
+
+/// Colours cycled through for service prefixes.
+const SERVICE_COLORS: &[Color] = &[
+    Color::Cyan,
+    Color::Magenta,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::BrightCyan,
+    Color::BrightMagenta,
+    Color::BrightGreen,
+];
+
+/// Command-line options for `fgp logs`.
+#[derive(Debug, Default)]
+pub struct LogOptions {
+    /// Minimum level to show (e.g. "warn")
+    pub level: Option<String>,
+    /// Show records at or after this time (duration like "1h" or a timestamp)
+    pub since: Option<String>,
+    /// Show records at or before this time (duration like "10m" or a timestamp)
+    pub until: Option<String>,
+    /// Regex matched against the message and target
+    pub grep: Option<String>,
+    /// Print records as JSON lines
+    pub json: bool,
+}
+
+/// Filters applied to parsed log records.
+///
+/// Unstructured lines (e.g. backtraces) follow the decision made for the
+/// record they continue.
+struct LogFilter {
+    level: Option<Level>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    grep: Option<Regex>,
+    last_matched: bool,
+}
+
+impl LogFilter {
+    fn from_options(options: &LogOptions) -> Result<Self> {
+        let level = options
+            .level
+            .as_deref()
+            .map(|l| l.parse::<Level>().map_err(anyhow::Error::msg))
+            .transpose()?;
+        let since = options.since.as_deref().map(parse_time_bound).transpose()?;
+        let until = options.until.as_deref().map(parse_time_bound).transpose()?;
+        let grep = options
+            .grep
+            .as_deref()
+            .map(|g| Regex::new(g).with_context(|| format!("Invalid --grep regex '{}'", g)))
+            .transpose()?;
+
+        Ok(Self {
+            level,
+            since,
+            until,
+            grep,
+            last_matched: true,
+        })
+    }
+
+    /// Decide whether to show a record, reading forwards.
+    fn matches(&mut self, record: &LogRecord) -> bool {
+        if record.is_continuation() {
+            return self.last_matched;
+        }
+
+        self.last_matched = self.evaluate(record);
+        self.last_matched
+    }
+
+    /// Whether a record is older than `--since`; everything before it is too.
+    fn is_before_since(&self, record: &LogRecord) -> bool {
+        match (self.since, record.timestamp) {
+            (Some(since), Some(ts)) => ts < since,
+            _ => false,
+        }
+    }
+
+    /// Decide whether a structured record passes, ignoring continuation state.
+    fn evaluate(&self, record: &LogRecord) -> bool {
+        if let Some(min) = self.level {
+            match record.level {
+                Some(level) if level >= min => {}
+                _ => return false,
+            }
+        }
+
+        if self.since.is_some() || self.until.is_some() {
+            let Some(ts) = record.timestamp else {
+                return false;
+            };
+            if self.since.is_some_and(|since| ts < since) {
+                return false;
+            }
+            if self.until.is_some_and(|until| ts > until) {
+                return false;
+            }
+        }
+
+        if let Some(re) = &self.grep {
+            let target_matches = record.target.as_deref().is_some_and(|t| re.is_match(t));
+            if !re.is_match(&record.message) && !target_matches {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Parse a `--since`/`--until` value: a duration ago ("1h") or a timestamp.
+fn parse_time_bound(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(duration) = parse_duration(value) {
+        return Ok(Utc::now() - duration);
+    }
+
+    if let Some(ts) = log_parser::parse_timestamp(value) {
+        return Ok(ts);
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if let Some(ts) = date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|d| Local.from_local_datetime(&d).single())
+        {
+            return Ok(ts.with_timezone(&Utc));
+        }
+    }
+
+    bail!(
+        "Invalid time '{}'. Use a duration (e.g. 1h, 30m) or a timestamp (e.g. 2024-01-15T10:00:00Z)",
+        value
+    )
+}
+
 /// Get the log file path for a service.
 fn log_file_path(service: &str) -> PathBuf {
     let base = shellexpand::tilde("~/.fgp/services");
@@ -17,9 +177,39 @@ fn log_file_path(service: &str) -> PathBuf {
         .join("daemon.log")
 }
 
-/// Run the logs command.
-pub fn run(service: &str, follow: bool, lines: usize) -> Result<()> {
+/// Run the logs command for one or more services (or all installed ones).
+pub fn run(
+    services: &[String],
+    all: bool,
+    follow: bool,
+    lines: usize,
+    options: &LogOptions,
+) -> Result<()> {
+    let services = if all {
+        let installed = installed_services()?;
+        if installed.is_empty() {
+            bail!("No services installed");
+        }
+        installed
+    } else {
+        services.to_vec()
+    };
+
+    match services.as_slice() {
+        [] => bail!("Specify a service name, or use --all"),
+        [service] => run_single(service, follow, lines, options),
+        _ => run_multi(&services, follow, lines, options),
+    }
+}
+
+/// Show a single service's logs.
+fn run_single(service: &str, follow: bool, lines: usize, options: &LogOptions) -> Result<()> {
     let log_path = log_file_path(service);
+    let mut filter = LogFilter::from_options(options)?;
+
+    if follow {
+        return follow_logs(&log_path, &mut filter, options.json);
+    }
 
     if !log_path.exists() {
         bail!(
@@ -29,61 +219,439 @@ pub fn run(service: &str, follow: bool, lines: usize) -> Result<()> {
         );
     }
 
+    tail_logs(&log_path, lines, &mut filter, options.json)
+}
+
+/// A service whose logs are interleaved with others.
+struct Source {
+    service: String,
+    /// Padded, coloured service name printed before each line
+    prefix: String,
+    filter: LogFilter,
+}
+
+impl Source {
+    fn new(service: &str, index: usize, width: usize, options: &LogOptions) -> Result<Self> {
+        let color = SERVICE_COLORS[index % SERVICE_COLORS.len()];
+        Ok(Self {
+            service: service.to_string(),
+            prefix: format!("{:<width$} |", service, width = width)
+                .color(color)
+                .bold()
+                .to_string(),
+            filter: LogFilter::from_options(options)?,
+        })
+    }
+}
+
+/// A structured record and its continuation lines, from one source.
+struct Entry {
+    source: usize,
+    timestamp: Option<DateTime<Utc>>,
+    records: Vec<LogRecord>,
+}
+
+/// Split a source's records (oldest first) into entries.
+///
+/// Entries without a timestamp of their own (e.g. leading continuation lines)
+/// take the previous one's, which is tracked across calls in `last_timestamp`.
+fn group_entries(
+    source: usize,
+    records: Vec<LogRecord>,
+    last_timestamp: &mut Option<DateTime<Utc>>,
+) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for record in records {
+        match entries.last_mut() {
+            Some(entry) if record.is_continuation() => entry.records.push(record),
+            _ => {
+                if let Some(ts) = record.timestamp {
+                    *last_timestamp = Some(ts);
+                }
+                entries.push(Entry {
+                    source,
+                    timestamp: *last_timestamp,
+                    records: vec![record],
+                });
+            }
+        }
+    }
+
+    entries
+}
+
+/// Show several services' logs interleaved by timestamp.
+fn run_multi(services: &[String], follow: bool, lines: usize, options: &LogOptions) -> Result<()> {
+    let width = services.iter().map(|s| s.len()).max().unwrap_or(0);
+    let mut sources = services
+        .iter()
+        .enumerate()
+        .map(|(i, service)| Source::new(service, i, width, options))
+        .collect::<Result<Vec<_>>>()?;
+
     if follow {
-        follow_logs(&log_path)?;
-    } else {
-        tail_logs(&log_path, lines)?;
+        return follow_multi(&mut sources, options.json);
+    }
+
+    let mut entries: Vec<Entry> = Vec::new();
+    for (i, source) in sources.iter_mut().enumerate() {
+        let path = log_file_path(&source.service);
+        if !path.exists() {
+            eprintln!(
+                "{} No logs found for service '{}'",
+                "!".yellow().bold(),
+                source.service
+            );
+            continue;
+        }
+        let records = tail_records(&path, lines, &mut source.filter)?;
+        entries.extend(group_entries(i, records, &mut None));
+    }
+
+    // Stable, so each service's own order is kept for equal timestamps
+    entries.sort_by_key(|e| e.timestamp);
+
+    let merged: Vec<(usize, LogRecord)> = entries
+        .into_iter()
+        .flat_map(|e| e.records.into_iter().map(move |r| (e.source, r)))
+        .collect();
+    let skip = merged.len().saturating_sub(lines);
+
+    for (source, record) in merged.into_iter().skip(skip) {
+        print_service_record(&sources[source], &record, options.json);
+    }
+
+    Ok(())
+}
+
+/// Follow several services' logs at once.
+///
+/// Lines that arrive within the same poll are merged by timestamp.
+fn follow_multi(sources: &mut [Source], json: bool) -> Result<()> {
+    if !json {
+        println!(
+            "{} Following logs for {}... (press Ctrl+C to exit)",
+            "→".blue().bold(),
+            sources
+                .iter()
+                .map(|s| s.service.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let mut followers: Vec<LogFollower> = sources
+        .iter()
+        .map(|s| LogFollower::new(log_file_path(&s.service)))
+        .collect();
+    let mut last_timestamps: Vec<Option<DateTime<Utc>>> = vec![None; sources.len()];
+
+    loop {
+        let mut entries: Vec<Entry> = Vec::new();
+
+        for (i, (source, follower)) in sources.iter_mut().zip(&mut followers).enumerate() {
+            let mut records = Vec::new();
+            while records.len() < FOLLOW_BATCH_LINES {
+                match follower.next_line() {
+                    Ok(Some(line)) => {
+                        let record = log_parser::parse_line(&line);
+                        if source.filter.matches(&record) {
+                            records.push(record);
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        eprintln!(
+                            "{} Read error for {}: {}",
+                            "✗".red().bold(),
+                            source.service,
+                            e
+                        );
+                        break;
+                    }
+                }
+            }
+            entries.extend(group_entries(i, records, &mut last_timestamps[i]));
+        }
+
+        if entries.is_empty() {
+            thread::sleep(FOLLOW_POLL_INTERVAL);
+            continue;
+        }
+
+        entries.sort_by_key(|e| e.timestamp);
+        for entry in entries {
+            for record in &entry.records {
+                print_service_record(&sources[entry.source], record, json);
+            }
+        }
     }
+}
 
+/// Print a record from one of several services.
+fn print_service_record(source: &Source, record: &LogRecord, json: bool) {
+    if json {
+        if let Ok(serde_json::Value::Object(mut obj)) = serde_json::to_value(record) {
+            obj.insert(
+                "service".to_string(),
+                serde_json::Value::String(source.service.clone()),
+            );
+            println!("{}", serde_json::Value::Object(obj));
+        }
+        return;
+    }
+
+    println!("{} {}", source.prefix, render_record(record));
+}
+
+/// The last N raw lines of a service's log, reaching into rotated segments.
+pub fn recent_lines(service: &str, lines: usize) -> Result<Vec<String>> {
+    let path = log_file_path(service);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut filter = LogFilter::from_options(&LogOptions::default())?;
+    Ok(tail_records(&path, lines, &mut filter)?
+        .into_iter()
+        .map(|r| r.raw)
+        .collect())
+}
+
+/// Display the last N matching lines of the log file.
+fn tail_logs(path: &Path, lines: usize, filter: &mut LogFilter, json: bool) -> Result<()> {
+    for record in tail_records(path, lines, filter)? {
+        print_record(&record, json);
+    }
     Ok(())
 }
 
-/// Display the last N lines of the log file.
-fn tail_logs(path: &PathBuf, lines: usize) -> Result<()> {
+/// Collect the last N matching records, oldest first.
+///
+/// The current log is read backwards in blocks, so only the tail is touched.
+/// If it holds fewer than N matches, rotated segments (`daemon.log.1`,
+/// `daemon.log.2.gz`, ...) are read from newest to oldest. Reading stops at the
+/// first record older than `--since`.
+fn tail_records(path: &Path, lines: usize, filter: &mut LogFilter) -> Result<Vec<LogRecord>> {
+    // Newest first; reversed at the end
+    let mut collected: Vec<LogRecord> = Vec::new();
+
     let file = File::open(path).context("Failed to open log file")?;
-    let reader = BufReader::new(file);
+    let mut reached_since = collect_backwards(file, lines, filter, &mut collected)?;
 
-    // Read all lines and keep the last N
-    let all_lines: Vec<String> = reader.lines().map_while(Result::ok).collect();
-    let start = if all_lines.len() > lines {
-        all_lines.len() - lines
-    } else {
-        0
+    for segment in rotated_segments(path) {
+        if reached_since || collected.len() >= lines {
+            break;
+        }
+        let needed = lines - collected.len();
+        let file = File::open(&segment)
+            .with_context(|| format!("Failed to open {}", segment.display()))?;
+
+        if segment.extension().is_some_and(|e| e == "gz") {
+            // Compressed segments can't be read backwards; stream them, keeping the last N
+            let reader = BufReader::new(GzDecoder::new(file));
+            let mut window: VecDeque<LogRecord> = VecDeque::with_capacity(needed);
+            filter.last_matched = true;
+            for line in reader.lines() {
+                let line = line.with_context(|| format!("Failed to read {}", segment.display()))?;
+                let record = log_parser::parse_line(&line);
+                reached_since |= filter.is_before_since(&record);
+                if filter.matches(&record) {
+                    if window.len() == needed {
+                        window.pop_front();
+                    }
+                    window.push_back(record);
+                }
+            }
+            collected.extend(window.into_iter().rev());
+        } else {
+            reached_since = collect_backwards(file, needed, filter, &mut collected)?;
+        }
+    }
+
+    collected.truncate(lines);
+    collected.reverse();
+    Ok(collected)
+}
+
+/// Read a log file from the end, pushing matching records (newest first) into
+/// `out` until it holds `limit` more records or the file is exhausted.
+///
+/// Returns true if it stopped at a record older than `--since`.
+fn collect_backwards(
+    file: File,
+    limit: usize,
+    filter: &LogFilter,
+    out: &mut Vec<LogRecord>,
+) -> Result<bool> {
+    let target = out.len() + limit;
+    // Continuation lines seen since the last structured record, newest first
+    let mut pending: Vec<LogRecord> = Vec::new();
+
+    for line in ReverseLines::new(file, TAIL_BLOCK_SIZE)? {
+        let record = log_parser::parse_line(&line?);
+
+        // A continuation belongs to the structured record before it, which we
+        // haven't seen yet when reading backwards
+        if record.is_continuation() {
+            pending.push(record);
+            continue;
+        }
+
+        if filter.is_before_since(&record) {
+            return Ok(true);
+        }
+
+        if filter.evaluate(&record) {
+            out.append(&mut pending);
+            out.push(record);
+        } else {
+            pending.clear();
+        }
+
+        if out.len() >= target {
+            return Ok(false);
+        }
+    }
+
+    // Lines before the first record continue one from an older segment; keep them
+    out.append(&mut pending);
+    Ok(false)
+}
+
+/// Rotated segments next to a log file, newest first
+/// (`daemon.log.1`, `daemon.log.2.gz`, ...).
+fn rotated_segments(path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
     };
 
-    for line in &all_lines[start..] {
-        print_log_line(line);
+    let prefix = format!("{}.", name);
+    let mut segments: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_str()?.to_string();
+            let suffix = file_name.strip_prefix(&prefix)?;
+            let index = suffix.strip_suffix(".gz").unwrap_or(suffix).parse().ok()?;
+            Some((index, entry.path()))
+        })
+        .collect();
+
+    segments.sort_by_key(|(index, _)| *index);
+    segments.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Iterates over a file's lines from last to first, reading fixed-size blocks
+/// backwards from the end.
+struct ReverseLines {
+    file: File,
+    /// Offset of the first byte in `buf`
+    pos: u64,
+    buf: Vec<u8>,
+    block_size: u64,
+}
+
+impl ReverseLines {
+    fn new(mut file: File, block_size: u64) -> std::io::Result<Self> {
+        let len = file.seek(SeekFrom::End(0))?;
+        let mut lines = Self {
+            file,
+            pos: len,
+            buf: Vec::new(),
+            block_size,
+        };
+
+        // Don't report the empty "line" after a trailing newline
+        lines.fill()?;
+        if lines.buf.last() == Some(&b'\n') {
+            lines.buf.pop();
+        }
+        Ok(lines)
     }
 
-    Ok(())
+    /// Prepend the previous block to the buffer.
+    fn fill(&mut self) -> std::io::Result<()> {
+        let size = self.block_size.min(self.pos);
+        if size == 0 {
+            return Ok(());
+        }
+        self.pos -= size;
+        self.file.seek(SeekFrom::Start(self.pos))?;
+
+        let mut block = vec![0u8; size as usize];
+        self.file.read_exact(&mut block)?;
+        block.append(&mut self.buf);
+        self.buf = block;
+        Ok(())
+    }
 }
 
-/// Follow log output in real-time (like tail -f).
-fn follow_logs(path: &PathBuf) -> Result<()> {
-    let mut file = File::open(path).context("Failed to open log file")?;
+impl Iterator for ReverseLines {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(i) = self.buf.iter().rposition(|&b| b == b'\n') {
+                let line = self.buf.split_off(i + 1);
+                self.buf.truncate(i);
+                return Some(Ok(decode_line(&line)));
+            }
+
+            if self.pos == 0 {
+                if self.buf.is_empty() {
+                    return None;
+                }
+                let line = std::mem::take(&mut self.buf);
+                return Some(Ok(decode_line(&line)));
+            }
 
-    // Seek to end of file
-    file.seek(SeekFrom::End(0))?;
+            if let Err(e) = self.fill() {
+                return Some(Err(e));
+            }
+        }
+    }
+}
 
-    println!(
-        "{} Following logs... (press Ctrl+C to exit)",
-        "→".blue().bold()
-    );
+/// Decode a raw line, dropping a trailing carriage return.
+fn decode_line(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
 
-    let mut reader = BufReader::new(file);
-    let mut line = String::new();
+/// Follow log output in real-time (like tail -f).
+///
+/// Survives log rotation and truncation, and waits for the file to appear
+/// if the daemon hasn't written it yet.
+fn follow_logs(path: &Path, filter: &mut LogFilter, json: bool) -> Result<()> {
+    if !json {
+        println!(
+            "{} Following logs... (press Ctrl+C to exit)",
+            "→".blue().bold()
+        );
+    }
+
+    let mut follower = LogFollower::new(path.to_path_buf());
+    if !path.exists() && !json {
+        println!(
+            "{} Waiting for {} to be created...",
+            "…".dimmed(),
+            path.display()
+        );
+    }
 
     loop {
-        line.clear();
-        match reader.read_line(&mut line) {
-            Ok(0) => {
-                // No new data, wait and try again
-                thread::sleep(Duration::from_millis(100));
-            }
-            Ok(_) => {
-                // Got new data
-                print_log_line(line.trim_end());
+        match follower.next_line() {
+            Ok(Some(line)) => {
+                let record = log_parser::parse_line(&line);
+                if filter.matches(&record) {
+                    print_record(&record, json);
+                }
             }
+            Ok(None) => thread::sleep(FOLLOW_POLL_INTERVAL),
             Err(e) => {
                 eprintln!("{} Read error: {}", "✗".red().bold(), e);
                 break;
@@ -94,44 +662,272 @@ fn follow_logs(path: &PathBuf) -> Result<()> {
     Ok(())
 }
 
-/// Detect log level from a line using case-insensitive search.
-/// Returns the detected level or None for INFO/unknown.
-fn detect_log_level(line: &str) -> Option<&'static str> {
-    // Use case-insensitive byte search to avoid allocation
-    let bytes = line.as_bytes();
-
-    // Check for common log level patterns in order of severity
-    if contains_case_insensitive(bytes, b"ERROR") {
-        Some("ERROR")
-    } else if contains_case_insensitive(bytes, b"WARN") {
-        Some("WARN")
-    } else if contains_case_insensitive(bytes, b"DEBUG") {
-        Some("DEBUG")
-    } else if contains_case_insensitive(bytes, b"TRACE") {
-        Some("TRACE")
-    } else {
-        None // INFO or other
+/// Tails a log file across rotation (file replaced) and truncation (copytruncate).
+struct LogFollower {
+    path: PathBuf,
+    reader: Option<BufReader<File>>,
+    /// (device, inode) of the open file
+    identity: (u64, u64),
+    /// Bytes consumed from the open file
+    pos: u64,
+    /// A trailing line the writer hasn't finished yet
+    partial: String,
+}
+
+impl LogFollower {
+    /// Create a follower that starts at the current end of `path`.
+    fn new(path: PathBuf) -> Self {
+        let mut follower = Self {
+            path,
+            reader: None,
+            identity: (0, 0),
+            pos: 0,
+            partial: String::new(),
+        };
+        follower.open(true);
+        follower
+    }
+
+    /// Open the file at `path`, either at its end or its start.
+    fn open(&mut self, at_end: bool) -> bool {
+        let Ok(mut file) = File::open(&self.path) else {
+            return false;
+        };
+        let Ok(meta) = file.metadata() else {
+            return false;
+        };
+
+        self.pos = if at_end {
+            file.seek(SeekFrom::End(0)).unwrap_or(0)
+        } else {
+            0
+        };
+        self.identity = (meta.dev(), meta.ino());
+        self.reader = Some(BufReader::new(file));
+        self.partial.clear();
+        true
+    }
+
+    /// Read the next complete line, or `None` if there is nothing new yet.
+    fn next_line(&mut self) -> std::io::Result<Option<String>> {
+        let Some(reader) = self.reader.as_mut() else {
+            // File didn't exist yet; once it does, read it from the start
+            self.open(false);
+            return Ok(None);
+        };
+
+        let mut line = String::new();
+        let n = reader.read_line(&mut line)?;
+        if n > 0 {
+            self.pos += n as u64;
+            self.partial.push_str(&line);
+            if self.partial.ends_with('\n') {
+                let complete = std::mem::take(&mut self.partial);
+                return Ok(Some(complete.trim_end().to_string()));
+            }
+            return Ok(None);
+        }
+
+        // At EOF: check whether the file was rotated or truncated
+        match fs::metadata(&self.path) {
+            Ok(meta) if (meta.dev(), meta.ino()) != self.identity => {
+                self.notice("log rotated, reopening");
+                self.open(false);
+            }
+            Ok(meta) if meta.len() < self.pos => {
+                self.notice("log truncated, reading from start");
+                self.open(false);
+            }
+            // Rotated away and not recreated yet; keep the old handle until it is
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn notice(&self, message: &str) {
+        eprintln!(
+            "{}",
+            format!("-- {}: {} --", self.path.display(), message).dimmed()
+        );
     }
 }
 
-/// Case-insensitive byte search without allocation.
-fn contains_case_insensitive(haystack: &[u8], needle: &[u8]) -> bool {
-    if needle.is_empty() || haystack.len() < needle.len() {
-        return needle.is_empty();
+/// Print a record, either as JSON or colour-coded by its parsed level.
+fn print_record(record: &LogRecord, json: bool) {
+    if json {
+        if let Ok(line) = serde_json::to_string(record) {
+            println!("{}", line);
+        }
+        return;
     }
-    haystack
-        .windows(needle.len())
-        .any(|window| window.eq_ignore_ascii_case(needle))
+
+    println!("{}", render_record(record));
 }
 
-/// Print a log line with color-coding by level.
-fn print_log_line(line: &str) {
-    let colored_line = match detect_log_level(line) {
-        Some("ERROR") => line.red().to_string(),
-        Some("WARN") => line.yellow().to_string(),
-        Some("DEBUG") | Some("TRACE") => line.dimmed().to_string(),
-        _ => line.to_string(), // INFO or other
+/// Render a record as text, colour-coded by its parsed level.
+fn render_record(record: &LogRecord) -> String {
+    // JSON records are rendered in a readable layout; text lines are kept verbatim
+    let text = if record.format == LogFormat::Json {
+        let mut text = String::new();
+        if let Some(ts) = record.timestamp {
+            text.push_str(
+                &ts.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S%.3f ")
+                    .to_string(),
+            );
+        }
+        if let Some(level) = record.level {
+            text.push_str(&format!("{:>5} ", level));
+        }
+        if let Some(target) = &record.target {
+            text.push_str(&format!("{}: ", target));
+        }
+        text.push_str(&record.message);
+        text
+    } else {
+        record.raw.clone()
     };
 
-    println!("{}", colored_line);
+    match record.level {
+        Some(Level::Error) => text.red().to_string(),
+        Some(Level::Warn) => text.yellow().to_string(),
+        Some(Level::Debug) | Some(Level::Trace) => text.dimmed().to_string(),
+        _ => text, // INFO or unstructured
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_reverse_lines_across_blocks() {
+        let mut file = tempfile::tempfile().unwrap();
+        write!(file, "first\nsecond line\r\n\nfourth\n").unwrap();
+
+        let lines: Vec<String> = ReverseLines::new(file, 4)
+            .unwrap()
+            .map(|l| l.unwrap())
+            .collect();
+        assert_eq!(lines, vec!["fourth", "", "second line", "first"]);
+    }
+
+    #[test]
+    fn test_tail_reads_rotated_gz_segment() {
+        let dir = tempfile::tempdir().unwrap();
+        let current = dir.path().join("daemon.log");
+        fs::write(&current, "2024-01-15T10:00:03Z  INFO a: three\n").unwrap();
+
+        let gz = File::create(dir.path().join("daemon.log.1.gz")).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(gz, flate2::Compression::default());
+        encoder
+            .write_all(b"2024-01-15T10:00:01Z  INFO a: one\n2024-01-15T10:00:02Z ERROR a: two\n  at frame\n")
+            .unwrap();
+        encoder.finish().unwrap();
+
+        let mut filter = LogFilter::from_options(&LogOptions::default()).unwrap();
+        let records = tail_records(&current, 3, &mut filter).unwrap();
+        let messages: Vec<&str> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["two", "  at frame", "three"]);
+    }
+
+    #[test]
+    fn test_tail_stops_at_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let current = dir.path().join("daemon.log");
+        fs::write(
+            &current,
+            "2024-01-15T09:00:00Z  INFO a: old\n  at frame\n2024-01-15T10:00:01Z  INFO a: new\n",
+        )
+        .unwrap();
+        // Unreadable, so the test fails if the scan reaches it
+        fs::write(dir.path().join("daemon.log.1.gz"), "not gzip").unwrap();
+
+        let options = LogOptions {
+            since: Some("2024-01-15T10:00:00Z".to_string()),
+            ..Default::default()
+        };
+        let mut filter = LogFilter::from_options(&options).unwrap();
+        let records = tail_records(&current, 10, &mut filter).unwrap();
+        let messages: Vec<&str> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["new"]);
+    }
+
+    /// Read every line the follower can currently see.
+    fn drain(follower: &mut LogFollower) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut idle = 0;
+        while idle < 3 {
+            match follower.next_line().unwrap() {
+                Some(line) => {
+                    lines.push(line);
+                    idle = 0;
+                }
+                None => idle += 1,
+            }
+        }
+        lines
+    }
+
+    #[test]
+    fn test_follower_survives_rotation_and_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        let append = |text: &str| {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        };
+
+        append("before\n");
+        let mut follower = LogFollower::new(path.clone());
+        assert!(drain(&mut follower).is_empty());
+
+        append("one\npart");
+        assert_eq!(drain(&mut follower), vec!["one"]);
+        append("ial\n");
+        assert_eq!(drain(&mut follower), vec!["partial"]);
+
+        // Rotation: lines written just before the rename are still read from
+        // the old file, then the new file is read from its start
+        append("two\n");
+        fs::rename(&path, dir.path().join("daemon.log.1")).unwrap();
+        append("three\nfour\n");
+        assert_eq!(drain(&mut follower), vec!["two", "three", "four"]);
+
+        // copytruncate: the file shrinks in place
+        fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        append("five\n");
+        assert_eq!(drain(&mut follower), vec!["five"]);
+    }
+
+    #[test]
+    fn test_group_entries_keeps_continuations_with_their_record() {
+        let records = vec![
+            log_parser::parse_line("  leftover frame"),
+            log_parser::parse_line("2024-01-15T10:00:01Z ERROR a: failed"),
+            log_parser::parse_line("  at frame"),
+            log_parser::parse_line("2024-01-15T10:00:02Z  INFO a: retrying"),
+        ];
+
+        let mut last = log_parser::parse_timestamp("2024-01-15T10:00:00Z");
+        let entries = group_entries(0, records, &mut last);
+
+        let sizes: Vec<usize> = entries.iter().map(|e| e.records.len()).collect();
+        assert_eq!(sizes, vec![1, 2, 1]);
+        assert_eq!(
+            entries[0].timestamp,
+            log_parser::parse_timestamp("2024-01-15T10:00:00Z")
+        );
+        assert_eq!(last, log_parser::parse_timestamp("2024-01-15T10:00:02Z"));
+    }
 }
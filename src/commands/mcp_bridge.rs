@@ -3,18 +3,38 @@
 //! Expose FGP daemons as MCP servers for compatibility with Claude Desktop,
 //! Cline, Continue, and other MCP-compatible tools.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
+use std::collections::HashMap;
 use std::fs;
 use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 // Use shared helpers from parent module
-use super::{fgp_services_dir, service_socket_path};
+use super::{
+    fgp_services_dir, mcp_prompts, mcp_resources, mcp_workflows, methods, service_socket_path,
+};
 
 /// Maximum retries when waiting for daemon to start.
 const MAX_START_RETRIES: u32 = 10;
 /// Delay between health check retries (ms).
 const RETRY_DELAY_MS: u64 = 100;
+/// How long to wait for a daemon's method list.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long a failed or timed-out probe is remembered before retrying.
+const PROBE_RETRY_AFTER: Duration = Duration::from_secs(30);
+/// How often the services directory is checked for daemons starting or stopping.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+/// How often a waiting call checks whether it was cancelled.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Protocol revisions the bridge speaks, newest first.
+pub const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+/// Longest tool name MCP clients accept (names must match `^[a-zA-Z0-9_-]{1,64}$`).
+pub const MAX_TOOL_NAME_LEN: usize = 64;
 
 /// Validate that a daemon name contains only safe characters.
 /// Prevents path traversal and shell injection attacks.
@@ -29,142 +49,829 @@ fn is_valid_daemon_name(name: &str) -> bool {
             .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
 }
 
+/// Which daemons a bridge exposes.
+#[derive(Debug, Default, Clone)]
+pub struct Scope {
+    services: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Scope {
+    /// Expose `services` (all installed daemons if empty), minus `exclude`.
+    pub fn new(services: Vec<String>, exclude: Vec<String>) -> Result<Self> {
+        for name in services.iter().chain(&exclude) {
+            if !is_valid_daemon_name(name) {
+                bail!("Invalid daemon name: {}", name);
+            }
+        }
+        Ok(Self { services, exclude })
+    }
+
+    /// Warn about requested services that aren't installed.
+    ///
+    /// Goes to stderr, since stdout carries the protocol in stdio mode.
+    pub fn warn_missing(&self) {
+        for name in &self.services {
+            if !fgp_services_dir().join(name).exists() {
+                eprintln!(
+                    "{} Service '{}' is not installed; it will have no tools",
+                    "!".yellow().bold(),
+                    name
+                );
+            }
+        }
+    }
+
+    /// Whether `daemon` is exposed by this server.
+    pub fn allows(&self, daemon: &str) -> bool {
+        (self.services.is_empty() || self.services.iter().any(|s| s == daemon))
+            && !self.exclude.iter().any(|s| s == daemon)
+    }
+
+    /// Whether a skill using `daemons` belongs on this server: it uses a
+    /// daemon in scope, or needs none and the server isn't limited to
+    /// particular services.
+    pub fn exposes_skill(&self, daemons: &[&str]) -> bool {
+        if daemons.is_empty() {
+            return self.services.is_empty();
+        }
+        daemons.iter().any(|d| self.allows(d))
+    }
+
+    /// Installed daemons in scope.
+    pub fn daemons(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(fgp_services_dir())
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .filter(|name| self.allows(name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+}
+
 /// Start the MCP bridge in stdio mode.
 ///
 /// This runs an MCP server that translates MCP tool calls to FGP daemon calls.
-pub fn serve() -> Result<()> {
-    // MCP uses JSON-RPC 2.0 over stdio
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
-
-    for line in stdin.lock().lines() {
-        let line = line.context("Failed to read from stdin")?;
+/// Only daemons in `scope` are listed or callable. Requests run on `workers`
+/// threads, with at most `max_per_daemon` calls to any one daemon at a time,
+/// and replies are written as they complete.
+pub fn serve(scope: Scope, workers: usize, max_per_daemon: usize) -> Result<()> {
+    scope.warn_missing();
+
+    let limits = Arc::new(DaemonLimits::new(max_per_daemon));
+    let catalogue = Catalogue::new(scope);
+    let session = Arc::new(Session::new(Arc::clone(&catalogue), limits));
+
+    // Tell the client when daemons start or stop
+    let notified = Arc::clone(&session);
+    thread::spawn(move || {
+        let mut seen = catalogue.generation();
+        loop {
+            let generation = catalogue.wait_for_change(seen, Duration::from_secs(60));
+            if generation != seen
+                && notified.is_initialized()
+                && write_message(&tools_list_changed()).is_err()
+            {
+                break;
+            }
+            seen = generation;
+        }
+    });
 
-        if line.is_empty() {
+    // Each job carries its cancel flag, registered when it was queued
+    let (jobs, queue) = mpsc::channel::<(serde_json::Value, Option<Arc<AtomicBool>>)>();
+    let queue = Arc::new(Mutex::new(queue));
+    let pool: Vec<_> = (0..workers.max(1))
+        .map(|_| {
+            let session = Arc::clone(&session);
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || loop {
+                let Ok((message, cancel)) = queue.lock().unwrap().recv() else {
+                    break;
+                };
+                if let Some(reply) = session.handle(message, cancel) {
+                    if write_message(&reply).is_err() {
+                        break;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    // MCP uses newline-delimited JSON-RPC 2.0 over stdio
+    let mut stdin = io::stdin().lock();
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        if stdin
+            .read_until(b'\n', &mut line)
+            .context("Failed to read from stdin")?
+            == 0
+        {
+            break;
+        }
+        if line.trim_ascii().is_empty() {
             continue;
         }
 
-        // Parse JSON-RPC request
-        let request: serde_json::Value =
-            serde_json::from_str(&line).context("Invalid JSON-RPC request")?;
-
-        let id = request.get("id").cloned();
-        let method = request["method"].as_str().unwrap_or("");
-
-        let response = match method {
-            "initialize" => handle_initialize(&request),
-            "tools/list" => handle_tools_list(id),
-            "tools/call" => handle_tools_call(&request),
-            _ => {
-                // Unknown method - return error
-                json_rpc_error(id, -32601, "Method not found")
+        let message = match Session::parse(&line) {
+            Ok(message) => message,
+            Err(reply) => {
+                write_message(&reply)?;
+                continue;
             }
         };
 
-        // Send response
-        writeln!(stdout, "{}", response)?;
-        stdout.flush()?;
+        // Notifications (including cancellations) and initialize are handled
+        // right away; everything else waits for a worker
+        let urgent = message.is_object()
+            && (message.get("id").is_none() || message["method"] == "initialize");
+        if urgent {
+            if let Some(reply) = session.handle(message, None) {
+                write_message(&reply)?;
+            }
+        } else {
+            // Register before queueing so a request can be cancelled while it
+            // waits for a worker
+            let cancel = session.track(&message);
+            jobs.send((message, cancel))
+                .context("Worker pool stopped")?;
+        }
+    }
+
+    // Let in-flight requests finish and reply before exiting
+    drop(jobs);
+    for worker in pool {
+        let _ = worker.join();
     }
 
     Ok(())
 }
 
-/// Handle MCP initialize request.
-fn handle_initialize(request: &serde_json::Value) -> String {
-    let id = request.get("id").cloned();
+/// The notification sent when the tool list may have changed.
+pub fn tools_list_changed() -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/tools/list_changed"
+    })
+}
 
-    let result = serde_json::json!({
-        "protocolVersion": "2024-11-05",
-        "serverInfo": {
-            "name": "fgp-mcp-bridge",
-            "version": env!("CARGO_PKG_VERSION")
-        },
-        "capabilities": {
-            "tools": {}
+/// Write one message to stdout.
+fn write_message(message: &serde_json::Value) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", message)?;
+    stdout.flush()
+}
+
+/// Caps concurrent calls per daemon, across all sessions.
+pub struct DaemonLimits {
+    max_per_daemon: usize,
+    running: Mutex<HashMap<String, usize>>,
+    freed: Condvar,
+}
+
+/// A slot for one call to a daemon, released on drop.
+struct Permit {
+    limits: Arc<DaemonLimits>,
+    daemon: String,
+}
+
+impl DaemonLimits {
+    pub fn new(max_per_daemon: usize) -> Self {
+        Self {
+            max_per_daemon: max_per_daemon.max(1),
+            running: Mutex::new(HashMap::new()),
+            freed: Condvar::new(),
         }
-    });
+    }
 
-    json_rpc_response(id, result)
+    /// Wait for a free slot on `daemon`. Returns None if `cancel` is set first.
+    fn acquire(self: &Arc<Self>, daemon: &str, cancel: &AtomicBool) -> Option<Permit> {
+        let mut running = self.running.lock().unwrap();
+        while running.get(daemon).copied().unwrap_or(0) >= self.max_per_daemon {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            running = self
+                .freed
+                .wait_timeout(running, CANCEL_POLL_INTERVAL)
+                .unwrap()
+                .0;
+        }
+        *running.entry(daemon.to_string()).or_insert(0) += 1;
+
+        Some(Permit {
+            limits: Arc::clone(self),
+            daemon: daemon.to_string(),
+        })
+    }
 }
 
-/// Encode daemon and method into MCP tool name.
-/// Uses double underscore (__) as separator since daemon names can contain single underscores.
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut running = self.limits.running.lock().unwrap();
+        if let Some(count) = running.get_mut(&self.daemon) {
+            *count -= 1;
+            if *count == 0 {
+                running.remove(&self.daemon);
+            }
+        }
+        self.limits.freed.notify_all();
+    }
+}
+
+/// State of one MCP connection, shared by the stdio and HTTP transports.
+///
+/// Requests may be handled concurrently from several threads.
+pub struct Session {
+    catalogue: Arc<Catalogue>,
+    limits: Arc<DaemonLimits>,
+    /// Negotiated protocol revision, set once `initialize` is answered
+    protocol_version: Mutex<Option<String>>,
+    /// Cancellation flags of requests being handled, by serialized request id
+    in_flight: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl Session {
+    pub fn new(catalogue: Arc<Catalogue>, limits: Arc<DaemonLimits>) -> Self {
+        Self {
+            catalogue,
+            limits,
+            protocol_version: Mutex::new(None),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `initialize` has been answered.
+    pub fn is_initialized(&self) -> bool {
+        self.protocol_version.lock().unwrap().is_some()
+    }
+
+    /// Whether `initialize` settled on protocol revision `version`.
+    pub fn negotiated(&self, version: &str) -> bool {
+        self.protocol_version.lock().unwrap().as_deref() == Some(version)
+    }
+
+    /// Parse one line of input, or return the parse error to send.
+    fn parse(line: &[u8]) -> std::result::Result<serde_json::Value, serde_json::Value> {
+        serde_json::from_slice(line)
+            .map_err(|e| json_rpc_error(None, -32700, &format!("Parse error: {}", e)))
+    }
+
+    /// Register a cancel flag for a request before it is queued, so
+    /// `notifications/cancelled` finds it even before a worker picks it up.
+    pub fn track(&self, message: &serde_json::Value) -> Option<Arc<AtomicBool>> {
+        let id = message.get("id")?;
+        let is_request = message["jsonrpc"] == "2.0" && message["method"].is_string();
+        (is_request && (id.is_string() || id.is_i64() || id.is_u64()))
+            .then(|| self.register(&id.to_string()))
+    }
+
+    fn register(&self, key: &str) -> Arc<AtomicBool> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.in_flight
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), Arc::clone(&cancel));
+        cancel
+    }
+
+    /// Handle a message or batch, returning the reply to send, if any.
+    ///
+    /// `cancel` is the flag [`Session::track`] registered for a single request.
+    fn handle(
+        &self,
+        message: serde_json::Value,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Option<serde_json::Value> {
+        match message {
+            serde_json::Value::Array(batch) if batch.is_empty() => {
+                Some(json_rpc_error(None, -32600, "Invalid Request: empty batch"))
+            }
+            // 2025-06-18 removed batching again
+            serde_json::Value::Array(_) if self.negotiated("2025-06-18") => Some(json_rpc_error(
+                None,
+                -32600,
+                "Invalid Request: batches are not supported in protocol version 2025-06-18",
+            )),
+            // Batches (2025-03-26) get one array of replies, or nothing if
+            // they held only notifications
+            serde_json::Value::Array(batch) => {
+                let replies: Vec<serde_json::Value> = batch
+                    .into_iter()
+                    .filter_map(|message| self.handle_message(message))
+                    .collect();
+                (!replies.is_empty()).then_some(serde_json::Value::Array(replies))
+            }
+            message => self.handle_tracked(message, cancel),
+        }
+    }
+
+    /// Handle one JSON-RPC message. Notifications, responses and cancelled
+    /// requests get no reply.
+    pub fn handle_message(&self, message: serde_json::Value) -> Option<serde_json::Value> {
+        self.handle_tracked(message, None)
+    }
+
+    /// Like [`Session::handle_message`], with the cancel flag registered when
+    /// the request was queued, if any.
+    fn handle_tracked(
+        &self,
+        message: serde_json::Value,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Option<serde_json::Value> {
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(|m| m.as_str());
+
+        if method.is_none() && (message.get("result").is_some() || message.get("error").is_some()) {
+            // A response; the bridge never sends requests, so nothing is waiting
+            return None;
+        }
+        let Some(method) = method.filter(|_| message["jsonrpc"] == "2.0") else {
+            return Some(json_rpc_error(id, -32600, "Invalid Request"));
+        };
+
+        match id {
+            None => {
+                self.handle_notification(method, &message["params"]);
+                None
+            }
+            Some(id) if id.is_string() || id.is_i64() || id.is_u64() => {
+                let key = id.to_string();
+                let cancel = cancel.unwrap_or_else(|| self.register(&key));
+
+                // Requests cancelled while queued aren't run at all
+                let reply = (!cancel.load(Ordering::Relaxed))
+                    .then(|| self.handle_request(Some(id), method, &message, &cancel));
+
+                self.in_flight.lock().unwrap().remove(&key);
+                // A cancelled request gets no response
+                reply.filter(|_| !cancel.load(Ordering::Relaxed))
+            }
+            Some(_) => Some(json_rpc_error(
+                None,
+                -32600,
+                "Invalid Request: id must be a string or integer",
+            )),
+        }
+    }
+
+    fn handle_notification(&self, method: &str, params: &serde_json::Value) {
+        // notifications/initialized needs no action, and unknown
+        // notifications are ignored
+        if method == "notifications/cancelled" {
+            let key = params["requestId"].to_string();
+            if let Some(cancel) = self.in_flight.lock().unwrap().get(&key) {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn handle_request(
+        &self,
+        id: Option<serde_json::Value>,
+        method: &str,
+        request: &serde_json::Value,
+        cancel: &AtomicBool,
+    ) -> serde_json::Value {
+        if !self.is_initialized() && method != "initialize" && method != "ping" {
+            return json_rpc_error(id, -32600, "Server not initialized");
+        }
+
+        match method {
+            "initialize" => self.handle_initialize(id, &request["params"]),
+            "ping" => json_rpc_response(id, serde_json::json!({})),
+            "tools/list" => handle_tools_list(id, &self.catalogue),
+            "tools/call" => handle_tools_call(request, &self.catalogue, &self.limits, cancel),
+            "prompts/list" => json_rpc_response(id, mcp_prompts::list(self.catalogue.scope())),
+            "prompts/get" => match mcp_prompts::get(self.catalogue.scope(), &request["params"]) {
+                Ok(result) => json_rpc_response(id, result),
+                Err(e) => json_rpc_error(id, -32602, &format!("{:#}", e)),
+            },
+            "resources/list" => json_rpc_response(id, mcp_resources::list(self.catalogue.scope())),
+            "resources/templates/list" => {
+                json_rpc_response(id, serde_json::json!({ "resourceTemplates": [] }))
+            }
+            "resources/read" => {
+                match mcp_resources::read(self.catalogue.scope(), &request["params"]) {
+                    Ok(result) => json_rpc_response(id, result),
+                    Err(e) => json_rpc_error(id, -32002, &format!("{:#}", e)),
+                }
+            }
+            _ => json_rpc_error(id, -32601, &format!("Method not found: {}", method)),
+        }
+    }
+
+    /// Handle MCP initialize request, negotiating the protocol revision.
+    fn handle_initialize(
+        &self,
+        id: Option<serde_json::Value>,
+        params: &serde_json::Value,
+    ) -> serde_json::Value {
+        let mut protocol_version = self.protocol_version.lock().unwrap();
+        if protocol_version.is_some() {
+            return json_rpc_error(id, -32600, "Already initialized");
+        }
+        let Some(requested) = params["protocolVersion"].as_str() else {
+            return json_rpc_error(id, -32602, "Missing protocolVersion");
+        };
+
+        // Agree to the client's revision if we speak it; otherwise offer our
+        // latest and let the client decide whether to disconnect
+        let version = PROTOCOL_VERSIONS
+            .iter()
+            .find(|v| **v == requested)
+            .unwrap_or(&PROTOCOL_VERSIONS[0]);
+        *protocol_version = Some(version.to_string());
+
+        let result = serde_json::json!({
+            "protocolVersion": version,
+            "serverInfo": {
+                "name": "fgp-mcp-bridge",
+                "version": env!("CARGO_PKG_VERSION")
+            },
+            "capabilities": {
+                "tools": {
+                    "listChanged": true
+                },
+                "prompts": {
+                    "listChanged": false
+                },
+                "resources": {
+                    "subscribe": false,
+                    "listChanged": false
+                }
+            }
+        });
+
+        json_rpc_response(id, result)
+    }
+}
+
+/// Encode daemon and method into an MCP tool name (`fgp__<daemon>__<method>`).
+///
+/// Dots become underscores and other characters MCP doesn't allow are
+/// replaced, so the name is not reversible; `ToolTable` maps it back.
 fn encode_tool_name(daemon: &str, method: &str) -> String {
-    format!("fgp__{}__{}", daemon, method.replace('.', "_"))
+    let name = format!(
+        "fgp__{}__{}",
+        sanitize_tool_name(daemon),
+        sanitize_tool_name(method)
+    );
+    if name.len() > MAX_TOOL_NAME_LEN {
+        with_hash_suffix(&name, daemon, method)
+    } else {
+        name
+    }
 }
 
-/// Decode MCP tool name into daemon and method.
-/// Returns None if the format is invalid.
-fn decode_tool_name(tool_name: &str) -> Option<(String, String)> {
-    let stripped = tool_name.strip_prefix("fgp__")?;
-    let parts: Vec<&str> = stripped.splitn(2, "__").collect();
-    if parts.len() != 2 {
-        return None;
+pub fn sanitize_tool_name(part: &str) -> String {
+    part.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Truncate `name` to fit and append a hash of the daemon and method.
+pub fn with_hash_suffix(name: &str, daemon: &str, method: &str) -> String {
+    // FNV-1a: stable across builds, unlike DefaultHasher
+    let hash = format!("{}.{}", daemon, method)
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100000001b3)
+        });
+    let suffix = format!("_{:08x}", hash as u32);
+    let keep = name.len().min(MAX_TOOL_NAME_LEN - suffix.len());
+    format!("{}{}", &name[..keep], suffix)
+}
+
+/// Maps the tool names handed out by `tools/list` back to daemon methods.
+#[derive(Debug, Default)]
+struct ToolTable {
+    tools: HashMap<String, (String, String)>,
+    /// Problems found while naming tools, for the caller to report
+    collisions: Vec<String>,
+}
+
+impl ToolTable {
+    /// Name a daemon method and remember it.
+    ///
+    /// When two methods encode to the same name (e.g. `list_prs` and
+    /// `list.prs`), the later one gets a hashed name and the collision is
+    /// recorded. Returns None if no unique name could be found.
+    fn insert(&mut self, daemon: &str, method: &str) -> Option<String> {
+        let target = (daemon.to_string(), method.to_string());
+        let mut name = encode_tool_name(daemon, method);
+
+        if let Some(existing) = self.tools.get(&name) {
+            if *existing == target {
+                return Some(name);
+            }
+            let alternative = with_hash_suffix(&name, daemon, method);
+            self.collisions.push(format!(
+                "{}.{} and {}.{} both map to tool '{}'; exposing {}.{} as '{}'",
+                existing.0, existing.1, daemon, method, name, daemon, method, alternative
+            ));
+            name = alternative;
+
+            if self.tools.contains_key(&name) {
+                self.collisions.push(format!(
+                    "No unique tool name for {}.{}; skipping it",
+                    daemon, method
+                ));
+                return None;
+            }
+        }
+
+        self.tools.insert(name.clone(), target);
+        Some(name)
+    }
+
+    /// The daemon and method behind a tool name.
+    fn resolve(&self, tool_name: &str) -> Option<(String, String)> {
+        self.tools.get(tool_name).cloned()
     }
-    let daemon = parts[0].to_string();
-    let method = parts[1].replace('_', ".");
-    Some((daemon, method))
 }
 
-/// Handle MCP tools/list request.
-fn handle_tools_list(id: Option<serde_json::Value>) -> String {
-    let mut tools = Vec::new();
+/// When a daemon's tools were last listed: its socket and manifest
+/// modification times. A daemon starting, stopping or being reinstalled
+/// changes it.
+type Fingerprint = (Option<SystemTime>, Option<SystemTime>);
+
+fn fingerprint(daemon: &str) -> Fingerprint {
+    let modified = |path: PathBuf| fs::metadata(path).and_then(|m| m.modified()).ok();
+    (
+        modified(service_socket_path(daemon)),
+        modified(fgp_services_dir().join(daemon).join("manifest.json")),
+    )
+}
 
-    // Scan installed daemons and collect their methods
-    let services_dir = fgp_services_dir();
-    if services_dir.exists() {
-        if let Ok(entries) = fs::read_dir(&services_dir) {
-            for entry in entries.flatten() {
-                let name = entry.file_name().to_string_lossy().to_string();
-                let socket = service_socket_path(&name);
-
-                if socket.exists() {
-                    // Try to get methods from this daemon
-                    if let Ok(client) = fgp_daemon::FgpClient::new(&socket) {
-                        if let Ok(response) = client.methods() {
-                            if response.ok {
-                                if let Some(result) = response.result {
-                                    if let Some(methods) = result["methods"].as_array() {
-                                        for method in methods {
-                                            let method_name =
-                                                method["name"].as_str().unwrap_or("unknown");
-                                            let description = method["description"]
-                                                .as_str()
-                                                .unwrap_or("No description");
-
-                                            // Skip internal methods
-                                            if method_name == "health"
-                                                || method_name == "stop"
-                                                || method_name == "methods"
-                                            {
-                                                continue;
-                                            }
-
-                                            // Build input schema from method params
-                                            let input_schema = method
-                                                .get("params")
-                                                .cloned()
-                                                .unwrap_or(serde_json::json!({
-                                                    "type": "object",
-                                                    "properties": {}
-                                                }));
-
-                                            tools.push(serde_json::json!({
-                                                "name": encode_tool_name(&name, method_name),
-                                                "description": format!("[FGP:{}] {}", name, description),
-                                                "inputSchema": input_schema
-                                            }));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+/// A daemon's methods as of its last probe.
+struct CachedDaemon {
+    fingerprint: Fingerprint,
+    /// None if the probe failed or timed out
+    methods: Option<Vec<serde_json::Value>>,
+    probed_at: Instant,
+}
+
+impl CachedDaemon {
+    fn is_fresh(&self, fingerprint: &Fingerprint) -> bool {
+        self.fingerprint == *fingerprint
+            && (self.methods.is_some() || self.probed_at.elapsed() < PROBE_RETRY_AFTER)
+    }
+}
+
+#[derive(Default)]
+struct CatalogueState {
+    daemons: HashMap<String, CachedDaemon>,
+    tools: Vec<serde_json::Value>,
+    table: ToolTable,
+}
+
+/// The tools of the daemons in scope, cached between `tools/list` calls and
+/// shared by every session.
+///
+/// A background thread polls the services directory and bumps the
+/// generation when daemons start, stop or are reinstalled, so sessions can
+/// send `notifications/tools/list_changed`.
+pub struct Catalogue {
+    scope: Scope,
+    state: Mutex<CatalogueState>,
+    generation: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl Catalogue {
+    /// Create a catalogue and start watching the services directory.
+    pub fn new(scope: Scope) -> Arc<Self> {
+        let catalogue = Arc::new(Self {
+            scope,
+            state: Mutex::new(CatalogueState::default()),
+            generation: Mutex::new(0),
+            changed: Condvar::new(),
+        });
+
+        let watched = Arc::clone(&catalogue);
+        thread::spawn(move || watched.watch());
+
+        catalogue
+    }
+
+    pub fn scope(&self) -> &Scope {
+        &self.scope
+    }
+
+    fn watch(&self) {
+        let snapshot = || {
+            let daemons: Vec<(String, Fingerprint)> = self
+                .scope
+                .daemons()
+                .into_iter()
+                .map(|name| {
+                    let fingerprint = fingerprint(&name);
+                    (name, fingerprint)
+                })
+                .collect();
+            (daemons, mcp_workflows::fingerprint())
+        };
+
+        let mut last = snapshot();
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let current = snapshot();
+            if current != last {
+                last = current;
+                *self.generation.lock().unwrap() += 1;
+                self.changed.notify_all();
             }
         }
     }
 
+    /// Counter bumped whenever the daemons in scope or the workflows change.
+    pub fn generation(&self) -> u64 {
+        *self.generation.lock().unwrap()
+    }
+
+    /// Wait up to `timeout` for the generation to move past `seen`, returning
+    /// the current generation.
+    pub fn wait_for_change(&self, seen: u64, timeout: Duration) -> u64 {
+        let generation = self.generation.lock().unwrap();
+        *self
+            .changed
+            .wait_timeout_while(generation, timeout, |g| *g == seen)
+            .unwrap()
+            .0
+    }
+
+    /// Tools of the daemons in scope, probing only daemons that changed since
+    /// they were last listed.
+    fn tools(&self) -> Vec<serde_json::Value> {
+        let mut state = self.state.lock().unwrap();
+        let daemons = self.scope.daemons();
+
+        let fingerprints: HashMap<String, Fingerprint> = daemons
+            .iter()
+            .map(|name| (name.clone(), fingerprint(name)))
+            .collect();
+        let stale: Vec<String> = daemons
+            .iter()
+            .filter(|name| {
+                !state
+                    .daemons
+                    .get(*name)
+                    .is_some_and(|cached| cached.is_fresh(&fingerprints[*name]))
+            })
+            .cloned()
+            .collect();
+
+        if stale.is_empty() && state.daemons.len() == daemons.len() {
+            return state.tools.clone();
+        }
+
+        for (name, methods) in probe_daemons(&stale) {
+            state.daemons.insert(
+                name.clone(),
+                CachedDaemon {
+                    fingerprint: fingerprints[&name],
+                    methods,
+                    probed_at: Instant::now(),
+                },
+            );
+        }
+        state
+            .daemons
+            .retain(|name, _| fingerprints.contains_key(name));
+
+        // Rebuild in daemon order so tool names are stable
+        let mut table = ToolTable::default();
+        let mut tools = Vec::new();
+        for name in &daemons {
+            let methods = state.daemons[name].methods.as_deref().unwrap_or_default();
+            tools.extend(methods_to_tools(name, methods, &mut table));
+        }
+        for collision in &table.collisions {
+            eprintln!("{} {}", "!".yellow().bold(), collision);
+        }
+
+        state.table = table;
+        state.tools = tools;
+        state.tools.clone()
+    }
+
+    /// The daemon and method behind a tool name, listing again if the client
+    /// named a tool before listing or the daemons have changed since.
+    fn resolve(&self, tool_name: &str) -> Option<(String, String)> {
+        if let Some(resolved) = self.state.lock().unwrap().table.resolve(tool_name) {
+            return Some(resolved);
+        }
+        self.tools();
+        self.state.lock().unwrap().table.resolve(tool_name)
+    }
+}
+
+/// Fetch methods from several daemons in parallel, giving up on any that
+/// don't answer within `PROBE_TIMEOUT`.
+fn probe_daemons(names: &[String]) -> Vec<(String, Option<Vec<serde_json::Value>>)> {
+    let (done, results) = mpsc::channel();
+    for name in names {
+        let done = done.clone();
+        let name = name.clone();
+        thread::spawn(move || {
+            let methods = probe_daemon(&name);
+            let _ = done.send((name, methods));
+        });
+    }
+    drop(done);
+
+    let deadline = Instant::now() + PROBE_TIMEOUT;
+    let mut probed = Vec::new();
+    while probed.len() < names.len() {
+        match results.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(result) => probed.push(result),
+            Err(_) => break,
+        }
+    }
+
+    for name in names {
+        if !probed.iter().any(|(n, _)| n == name) {
+            eprintln!(
+                "{} {} didn't list its methods within {}s; skipping its tools",
+                "!".yellow().bold(),
+                name,
+                PROBE_TIMEOUT.as_secs()
+            );
+            probed.push((name.clone(), None));
+        }
+    }
+    probed
+}
+
+/// A daemon's methods, or an empty list if it isn't running.
+fn probe_daemon(name: &str) -> Option<Vec<serde_json::Value>> {
+    let socket = service_socket_path(name);
+    if !socket.exists() {
+        return Some(Vec::new());
+    }
+
+    let client = fgp_daemon::FgpClient::new(&socket).ok()?;
+    let response = client.methods().ok().filter(|r| r.ok)?;
+    Some(response.result?["methods"].as_array()?.clone())
+}
+
+/// Turn a daemon's methods into MCP tools, registering their names in `table`.
+fn methods_to_tools(
+    daemon: &str,
+    methods: &[serde_json::Value],
+    table: &mut ToolTable,
+) -> Vec<serde_json::Value> {
+    let mut tools = Vec::new();
+
+    for method in methods {
+        let method_name = method["name"].as_str().unwrap_or("unknown");
+        let description = method["description"].as_str().unwrap_or("No description");
+
+        // Skip internal methods
+        if method_name == "health" || method_name == "stop" || method_name == "methods" {
+            continue;
+        }
+
+        // Convert FGP param definitions to JSON Schema
+        let input_schema = methods::input_schema(&method["params"]);
+
+        let Some(tool_name) = table.insert(daemon, method_name) else {
+            continue;
+        };
+
+        let mut tool = serde_json::json!({
+            "name": tool_name,
+            "description": format!("[FGP:{}] {}", daemon, description),
+            "inputSchema": input_schema
+        });
+        if let Some(output_schema) = methods::output_schema(method) {
+            tool["outputSchema"] = output_schema;
+        }
+        tools.push(tool);
+    }
+
+    tools
+}
+
+/// Handle MCP tools/list request.
+fn handle_tools_list(id: Option<serde_json::Value>, catalogue: &Catalogue) -> serde_json::Value {
+    let mut tools = catalogue.tools();
+    tools.extend(mcp_workflows::tools(catalogue.scope()));
+
     // Add meta-tools
     tools.push(serde_json::json!({
         "name": "fgp_list_daemons",
@@ -213,8 +920,14 @@ fn handle_tools_list(id: Option<serde_json::Value>) -> String {
 }
 
 /// Handle MCP tools/call request.
-fn handle_tools_call(request: &serde_json::Value) -> String {
+fn handle_tools_call(
+    request: &serde_json::Value,
+    catalogue: &Catalogue,
+    limits: &Arc<DaemonLimits>,
+    cancel: &AtomicBool,
+) -> serde_json::Value {
     let id = request.get("id").cloned();
+    let scope = catalogue.scope();
     let params = &request["params"];
     let tool_name = params["name"].as_str().unwrap_or("");
     let arguments = params
@@ -224,70 +937,166 @@ fn handle_tools_call(request: &serde_json::Value) -> String {
 
     // Handle meta-tools
     if tool_name == "fgp_list_daemons" {
-        return handle_list_daemons(id);
-    } else if tool_name == "fgp_start_daemon" {
-        let daemon_name = arguments["name"].as_str().unwrap_or("");
-        return handle_start_daemon(id, daemon_name);
-    } else if tool_name == "fgp_stop_daemon" {
+        return handle_list_daemons(id, scope);
+    } else if tool_name == "fgp_start_daemon" || tool_name == "fgp_stop_daemon" {
         let daemon_name = arguments["name"].as_str().unwrap_or("");
-        return handle_stop_daemon(id, daemon_name);
+        if !scope.allows(daemon_name) {
+            return json_rpc_error(id, -32602, "Daemon is not exposed by this server");
+        }
+        return if tool_name == "fgp_start_daemon" {
+            handle_start_daemon(id, daemon_name)
+        } else {
+            handle_stop_daemon(id, daemon_name)
+        };
     }
 
-    // Parse tool name to extract daemon and method
-    let (daemon, method) = match decode_tool_name(tool_name) {
-        Some((d, m)) => (d, m),
-        None => {
-            return json_rpc_error(
-                id,
-                -32602,
-                "Invalid tool name format. Expected fgp__<daemon>__<method>",
-            )
-        }
+    if tool_name.starts_with(mcp_workflows::TOOL_PREFIX) {
+        return handle_workflow_call(id, tool_name, &arguments, scope, limits, cancel);
+    }
+
+    let Some((daemon, method)) = catalogue.resolve(tool_name) else {
+        return json_rpc_error(id, -32602, &format!("Unknown tool: {}", tool_name));
     };
 
     // Validate daemon name to prevent path traversal
     if !is_valid_daemon_name(&daemon) {
         return json_rpc_error(id, -32602, "Invalid daemon name");
     }
+    if !scope.allows(&daemon) {
+        return json_rpc_error(id, -32602, "Daemon is not exposed by this server");
+    }
+
+    // Wait for a free slot on this daemon
+    let Some(permit) = limits.acquire(&daemon, cancel) else {
+        return json_rpc_error(id, -32800, "Request cancelled");
+    };
 
-    // Call the daemon
+    if let Err(e) = ensure_running(&daemon) {
+        return tool_error(id, &e.to_string());
+    }
+
+    // Call on another thread so cancellation can stop waiting; the slot stays
+    // taken until the daemon actually answers
     let socket = service_socket_path(&daemon);
+    let (done, outcome) = mpsc::channel();
+    thread::spawn(move || {
+        let _permit = permit;
+        let response =
+            fgp_daemon::FgpClient::new(&socket).map(|client| client.call(&method, arguments));
+        let _ = done.send(response);
+    });
+    let response = match wait_cancellable(&id, &outcome, cancel) {
+        Ok(response) => response,
+        Err(reply) => return reply,
+    };
 
-    // Auto-start if needed with health polling
-    if !socket.exists() {
-        if let Err(e) = fgp_daemon::lifecycle::start_service(&daemon) {
-            return json_rpc_error(id, -32603, &format!("Failed to start daemon: {}", e));
+    // Daemon failures are tool results, so the model can read and recover
+    // from them; JSON-RPC errors are kept for protocol problems
+    match response {
+        Ok(Ok(response)) if response.ok => tool_result(id, &response.result.unwrap_or_default()),
+        Ok(Ok(response)) => {
+            let error_msg = response
+                .error
+                .map(|e| e.message)
+                .unwrap_or_else(|| "Unknown error".to_string());
+            tool_error(id, &error_msg)
+        }
+        Ok(Err(e)) => tool_error(id, &format!("Call failed: {}", e)),
+        Err(e) => tool_error(id, &format!("Failed to connect to daemon: {}", e)),
+    }
+}
+
+/// Run a workflow tool, holding a slot on each daemon it uses until it finishes.
+fn handle_workflow_call(
+    id: Option<serde_json::Value>,
+    tool_name: &str,
+    arguments: &serde_json::Value,
+    scope: &Scope,
+    limits: &Arc<DaemonLimits>,
+    cancel: &AtomicBool,
+) -> serde_json::Value {
+    let workflow = match mcp_workflows::prepare(scope, tool_name, arguments) {
+        Ok(workflow) => workflow,
+        Err(e) => return json_rpc_error(id, -32602, &format!("{:#}", e)),
+    };
+
+    let mut daemons: Vec<String> = workflow.steps.iter().map(|s| s.service.clone()).collect();
+    daemons.sort();
+    daemons.dedup();
+
+    // Take slots in name order, so two workflows can't each hold what the
+    // other is waiting for
+    let mut permits = Vec::new();
+    for daemon in &daemons {
+        if !is_valid_daemon_name(daemon) {
+            return json_rpc_error(id, -32602, "Invalid daemon name");
         }
-        // Poll for daemon readiness instead of fixed sleep
-        if !wait_for_daemon_ready(&daemon) {
-            return json_rpc_error(id, -32603, "Daemon started but not responding");
+        let Some(permit) = limits.acquire(daemon, cancel) else {
+            return json_rpc_error(id, -32800, "Request cancelled");
+        };
+        permits.push(permit);
+        if let Err(e) = ensure_running(daemon) {
+            return tool_error(id, &e.to_string());
         }
     }
 
-    match fgp_daemon::FgpClient::new(&socket) {
-        Ok(client) => match client.call(&method, arguments) {
-            Ok(response) if response.ok => {
-                let result = serde_json::json!({
-                    "content": [{
-                        "type": "text",
-                        "text": serde_json::to_string_pretty(&response.result).unwrap_or_default()
-                    }]
-                });
-                json_rpc_response(id, result)
+    let (done, outcome) = mpsc::channel();
+    thread::spawn(move || {
+        let _permits = permits;
+        let result = fgp_workflow::execute(&workflow);
+        let _ = done.send((workflow.name, result));
+    });
+    let (name, result) = match wait_cancellable(&id, &outcome, cancel) {
+        Ok(outcome) => outcome,
+        Err(reply) => return reply,
+    };
+
+    match result {
+        Ok(result) => tool_result(id, &mcp_workflows::result_json(&name, &result)),
+        Err(e) => tool_error(id, &format!("Workflow failed: {:#}", e)),
+    }
+}
+
+/// Wait for work running on another thread, or return the error reply if the
+/// request is cancelled or the thread dies first.
+fn wait_cancellable<T>(
+    id: &Option<serde_json::Value>,
+    outcome: &mpsc::Receiver<T>,
+    cancel: &AtomicBool,
+) -> Result<T, serde_json::Value> {
+    loop {
+        match outcome.recv_timeout(CANCEL_POLL_INTERVAL) {
+            Ok(value) => return Ok(value),
+            Err(mpsc::RecvTimeoutError::Timeout) if cancel.load(Ordering::Relaxed) => {
+                return Err(json_rpc_error(id.clone(), -32800, "Request cancelled"));
             }
-            Ok(response) => {
-                let error_msg = response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                json_rpc_error(id, -32603, &error_msg)
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(json_rpc_error(
+                    id.clone(),
+                    -32603,
+                    "Call failed: daemon client panicked",
+                ));
             }
-            Err(e) => json_rpc_error(id, -32603, &format!("Call failed: {}", e)),
-        },
-        Err(e) => json_rpc_error(id, -32603, &format!("Failed to connect to daemon: {}", e)),
+        }
     }
 }
 
+/// Start `daemon` if it isn't running and wait for it to answer.
+fn ensure_running(daemon: &str) -> Result<()> {
+    if service_socket_path(daemon).exists() {
+        return Ok(());
+    }
+    if let Err(e) = fgp_daemon::lifecycle::start_service(daemon) {
+        bail!("Failed to start daemon: {}", e);
+    }
+    // Poll for daemon readiness instead of fixed sleep
+    if !wait_for_daemon_ready(daemon) {
+        bail!("Daemon started but not responding");
+    }
+    Ok(())
+}
+
 /// Wait for a daemon to become ready by polling its health endpoint.
 fn wait_for_daemon_ready(daemon: &str) -> bool {
     let socket = service_socket_path(daemon);
@@ -305,50 +1114,37 @@ fn wait_for_daemon_ready(daemon: &str) -> bool {
 }
 
 /// Handle fgp_list_daemons meta-tool.
-fn handle_list_daemons(id: Option<serde_json::Value>) -> String {
-    let services_dir = fgp_services_dir();
+fn handle_list_daemons(id: Option<serde_json::Value>, scope: &Scope) -> serde_json::Value {
     let mut daemons = Vec::new();
 
-    if services_dir.exists() {
-        if let Ok(entries) = fs::read_dir(&services_dir) {
-            for entry in entries.flatten() {
-                let name = entry.file_name().to_string_lossy().to_string();
-                let socket = service_socket_path(&name);
-
-                let status = if socket.exists() {
-                    if let Ok(client) = fgp_daemon::FgpClient::new(&socket) {
-                        if client.health().is_ok() {
-                            "running"
-                        } else {
-                            "error"
-                        }
-                    } else {
-                        "error"
-                    }
-                } else {
-                    "stopped"
-                };
+    for name in scope.daemons() {
+        let socket = service_socket_path(&name);
 
-                daemons.push(serde_json::json!({
-                    "name": name,
-                    "status": status
-                }));
+        let status = if socket.exists() {
+            if let Ok(client) = fgp_daemon::FgpClient::new(&socket) {
+                if client.health().is_ok() {
+                    "running"
+                } else {
+                    "error"
+                }
+            } else {
+                "error"
             }
-        }
-    }
+        } else {
+            "stopped"
+        };
 
-    let result = serde_json::json!({
-        "content": [{
-            "type": "text",
-            "text": serde_json::to_string_pretty(&daemons).unwrap_or_default()
-        }]
-    });
+        daemons.push(serde_json::json!({
+            "name": name,
+            "status": status
+        }));
+    }
 
-    json_rpc_response(id, result)
+    tool_result(id, &serde_json::json!(daemons))
 }
 
 /// Handle fgp_start_daemon meta-tool.
-fn handle_start_daemon(id: Option<serde_json::Value>, name: &str) -> String {
+fn handle_start_daemon(id: Option<serde_json::Value>, name: &str) -> serde_json::Value {
     if !is_valid_daemon_name(name) {
         return json_rpc_error(id, -32602, "Invalid daemon name");
     }
@@ -363,12 +1159,12 @@ fn handle_start_daemon(id: Option<serde_json::Value>, name: &str) -> String {
             });
             json_rpc_response(id, result)
         }
-        Err(e) => json_rpc_error(id, -32603, &format!("Failed to start daemon: {}", e)),
+        Err(e) => tool_error(id, &format!("Failed to start daemon: {}", e)),
     }
 }
 
 /// Handle fgp_stop_daemon meta-tool.
-fn handle_stop_daemon(id: Option<serde_json::Value>, name: &str) -> String {
+fn handle_stop_daemon(id: Option<serde_json::Value>, name: &str) -> serde_json::Value {
     if !is_valid_daemon_name(name) {
         return json_rpc_error(id, -32602, "Invalid daemon name");
     }
@@ -383,31 +1179,65 @@ fn handle_stop_daemon(id: Option<serde_json::Value>, name: &str) -> String {
             });
             json_rpc_response(id, result)
         }
-        Err(e) => json_rpc_error(id, -32603, &format!("Failed to stop daemon: {}", e)),
+        Err(e) => tool_error(id, &format!("Failed to stop daemon: {}", e)),
     }
 }
 
+/// A successful `CallToolResult`: the value as pretty-printed text, plus
+/// `structuredContent`. That must be an object, so other values are wrapped
+/// as `{"result": value}`, matching [`methods::output_schema`].
+fn tool_result(id: Option<serde_json::Value>, value: &serde_json::Value) -> serde_json::Value {
+    let structured = if value.is_object() {
+        value.clone()
+    } else {
+        serde_json::json!({ "result": value })
+    };
+
+    json_rpc_response(
+        id,
+        serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": serde_json::to_string_pretty(value).unwrap_or_default()
+            }],
+            "structuredContent": structured
+        }),
+    )
+}
+
+/// A `CallToolResult` reporting a failed call.
+fn tool_error(id: Option<serde_json::Value>, message: &str) -> serde_json::Value {
+    json_rpc_response(
+        id,
+        serde_json::json!({
+            "content": [{ "type": "text", "text": message }],
+            "isError": true
+        }),
+    )
+}
+
 /// Create a JSON-RPC response.
-fn json_rpc_response(id: Option<serde_json::Value>, result: serde_json::Value) -> String {
-    let response = serde_json::json!({
+fn json_rpc_response(
+    id: Option<serde_json::Value>,
+    result: serde_json::Value,
+) -> serde_json::Value {
+    serde_json::json!({
         "jsonrpc": "2.0",
         "id": id,
         "result": result
-    });
-    serde_json::to_string(&response).unwrap_or_default()
+    })
 }
 
 /// Create a JSON-RPC error response.
-fn json_rpc_error(id: Option<serde_json::Value>, code: i32, message: &str) -> String {
-    let response = serde_json::json!({
+fn json_rpc_error(id: Option<serde_json::Value>, code: i32, message: &str) -> serde_json::Value {
+    serde_json::json!({
         "jsonrpc": "2.0",
         "id": id,
         "error": {
             "code": code,
             "message": message
         }
-    });
-    serde_json::to_string(&response).unwrap_or_default()
+    })
 }
 
 /// Register FGP with Claude Code.
@@ -444,11 +1274,11 @@ pub fn tools() -> Result<()> {
         return Ok(());
     }
 
-    let entries = fs::read_dir(&services_dir)?;
+    // Same order as `serve`, so collisions resolve to the same names
+    let mut table = ToolTable::default();
     let mut total_tools = 0;
 
-    for entry in entries.flatten() {
-        let name = entry.file_name().to_string_lossy().to_string();
+    for name in Scope::default().daemons() {
         let socket = service_socket_path(&name);
 
         println!("{}", name.cyan().bold());
@@ -476,11 +1306,10 @@ pub fn tools() -> Result<()> {
                                     continue;
                                 }
 
-                                println!(
-                                    "  {} - {}",
-                                    encode_tool_name(&name, method_name).green(),
-                                    description.dimmed()
-                                );
+                                let Some(tool_name) = table.insert(&name, method_name) else {
+                                    continue;
+                                };
+                                println!("  {} - {}", tool_name.green(), description.dimmed());
                                 total_tools += 1;
                             }
                         }
@@ -498,6 +1327,19 @@ pub fn tools() -> Result<()> {
         println!();
     }
 
+    let workflows = mcp_workflows::tools(&Scope::default());
+    if !workflows.is_empty() {
+        println!("{}", "Workflows".cyan().bold());
+        for tool in &workflows {
+            println!(
+                "  {} - {}",
+                tool["name"].as_str().unwrap_or_default().green(),
+                tool["description"].as_str().unwrap_or_default().dimmed()
+            );
+        }
+        println!();
+    }
+
     // Meta-tools
     println!("{}", "Meta-Tools".cyan().bold());
     println!(
@@ -507,8 +1349,68 @@ pub fn tools() -> Result<()> {
     println!("  {} - Start an FGP daemon", "fgp_start_daemon".green());
     println!("  {} - Stop an FGP daemon", "fgp_stop_daemon".green());
 
+    for collision in &table.collisions {
+        println!();
+        println!("{} {}", "!".yellow().bold(), collision);
+    }
+
     println!();
-    println!("Total: {} tools available", total_tools + 3);
+    println!(
+        "Total: {} tools available",
+        total_tools + workflows.len() + 3
+    );
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_table_round_trips_and_disambiguates() {
+        let mut table = ToolTable::default();
+
+        let dotted = table.insert("github", "list.prs").unwrap();
+        assert_eq!(dotted, "fgp__github__list_prs");
+        let underscored = table.insert("github", "list_prs").unwrap();
+        assert_ne!(underscored, dotted);
+        assert_eq!(table.collisions.len(), 1);
+
+        assert_eq!(
+            table.resolve(&dotted),
+            Some(("github".to_string(), "list.prs".to_string()))
+        );
+        assert_eq!(
+            table.resolve(&underscored),
+            Some(("github".to_string(), "list_prs".to_string()))
+        );
+        assert_eq!(table.insert("github", "list.prs").unwrap(), dotted);
+
+        let long = table
+            .insert("workspace", &"very.long.method".repeat(6))
+            .unwrap();
+        assert!(long.len() <= MAX_TOOL_NAME_LEN);
+        assert!(long
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'));
+        assert_eq!(table.resolve(&long).unwrap().0, "workspace");
+    }
+
+    #[test]
+    fn test_daemon_limits() {
+        let limits = Arc::new(DaemonLimits::new(2));
+        let cancelled = AtomicBool::new(true);
+        let waiting = AtomicBool::new(false);
+
+        let first = limits.acquire("gmail", &waiting).unwrap();
+        let _second = limits.acquire("gmail", &waiting).unwrap();
+        // Full: a cancelled waiter gives up, other daemons are unaffected
+        assert!(limits.acquire("gmail", &cancelled).is_none());
+        let _other = limits.acquire("github", &cancelled).unwrap();
+
+        let releaser = thread::spawn(move || drop(first));
+        assert!(limits.acquire("gmail", &waiting).is_some());
+        releaser.join().unwrap();
+    }
+}
@@ -0,0 +1,708 @@
+//! Streamable HTTP transport for the MCP bridge.
+//!
+//! Clients POST JSON-RPC messages to `/mcp` and get JSON or an SSE stream
+//! back, and can GET `/mcp` for a stream of server notifications; sessions
+//! are tracked with the `Mcp-Session-Id` header. Messages are handled by the
+//! same `Session` as the stdio bridge.
+//!
+//! Sessions that see no requests (and have no open stream) for
+//! `SESSION_IDLE_TIMEOUT` expire, and at most `MAX_CONNECTIONS` connections
+//! are served at once; further ones get 503.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::mcp_bridge::{
+    tools_list_changed, Catalogue, DaemonLimits, Scope, Session, PROTOCOL_VERSIONS,
+};
+
+/// The single MCP endpoint.
+const ENDPOINT: &str = "/mcp";
+/// Timeout for reading a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often an idle notification stream sends a keep-alive comment.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Largest request body accepted.
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+/// Longest request or header line accepted.
+const MAX_HEADER_LINE_BYTES: usize = 8 * 1024;
+/// Most header lines accepted in one request.
+const MAX_HEADERS: usize = 100;
+/// Most connections (and so handler threads) served at once.
+const MAX_CONNECTIONS: usize = 64;
+/// Most sessions kept at once.
+const MAX_SESSIONS: usize = 256;
+/// How long a session survives without requests or an open stream.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Shared by every connection.
+struct Server {
+    catalogue: Arc<Catalogue>,
+    limits: Arc<DaemonLimits>,
+    token: Option<String>,
+    sessions: Sessions,
+    connections: Arc<ConnectionLimit>,
+}
+
+/// Live sessions by ID, expiring those left idle.
+struct Sessions {
+    entries: Mutex<HashMap<String, (Arc<Session>, Instant)>>,
+    idle_timeout: Duration,
+    max: usize,
+}
+
+impl Sessions {
+    fn new(idle_timeout: Duration, max: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            idle_timeout,
+            max,
+        }
+    }
+
+    /// Look up a session, counting the lookup as activity.
+    fn get(&self, id: &str) -> Option<Arc<Session>> {
+        let mut entries = self.entries.lock().unwrap();
+        self.expire(&mut entries);
+        let (session, last_used) = entries.get_mut(id)?;
+        *last_used = Instant::now();
+        Some(Arc::clone(session))
+    }
+
+    /// Add a session, or return false if there are already too many.
+    fn insert(&self, id: String, session: Arc<Session>) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        self.expire(&mut entries);
+        if entries.len() >= self.max {
+            return false;
+        }
+        entries.insert(id, (session, Instant::now()));
+        true
+    }
+
+    fn remove(&self, id: &str) -> bool {
+        self.entries.lock().unwrap().remove(id).is_some()
+    }
+
+    fn expire(&self, entries: &mut HashMap<String, (Arc<Session>, Instant)>) {
+        entries.retain(|_, (_, last_used)| last_used.elapsed() < self.idle_timeout);
+    }
+}
+
+/// Counts open connections against a cap.
+struct ConnectionLimit {
+    active: AtomicUsize,
+    max: usize,
+}
+
+impl ConnectionLimit {
+    /// Take a slot, or `None` if all are in use. The slot is freed on drop.
+    fn acquire(self: &Arc<Self>) -> Option<ConnectionSlot> {
+        if self.active.fetch_add(1, Ordering::SeqCst) >= self.max {
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(ConnectionSlot(Arc::clone(self)))
+    }
+}
+
+struct ConnectionSlot(Arc<ConnectionLimit>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    /// Header names are lowercased
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// A complete (non-streaming) HTTP response.
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Response {
+    fn text(status: &'static str, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            headers: Vec::new(),
+            body: format!("{}\n", body),
+        }
+    }
+
+    fn json(status: &'static str, body: &serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    fn empty(status: &'static str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    fn with_header(mut self, name: &'static str, value: String) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    fn write_to(&self, stream: &mut TcpStream) -> io::Result<()> {
+        let mut head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            self.content_type,
+            self.body.len()
+        );
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        write!(stream, "{}\r\n{}", head, self.body)?;
+        stream.flush()
+    }
+}
+
+/// Serve the MCP bridge over Streamable HTTP on `listen` (e.g. "127.0.0.1:8931").
+///
+/// When `token` is set, every request must send `Authorization: Bearer <token>`.
+/// Each request runs on its own thread (up to `MAX_CONNECTIONS`), with at most
+/// `max_per_daemon` calls to any one daemon at a time across all sessions.
+pub fn serve(
+    scope: Scope,
+    listen: &str,
+    token: Option<String>,
+    max_per_daemon: usize,
+) -> Result<()> {
+    let listener =
+        TcpListener::bind(listen).with_context(|| format!("Failed to listen on {}", listen))?;
+    let addr = listener.local_addr()?;
+
+    scope.warn_missing();
+    println!(
+        "{} Serving MCP on http://{}{} (Ctrl+C to stop)",
+        "→".blue().bold(),
+        addr,
+        ENDPOINT
+    );
+    match &token {
+        Some(_) => println!("  Requests must send 'Authorization: Bearer <token>'"),
+        None if !addr.ip().is_loopback() => println!(
+            "  {} Listening on a non-loopback address without --token; anyone who can reach it can call your daemons",
+            "!".yellow().bold()
+        ),
+        None => {}
+    }
+
+    let server = Arc::new(Server {
+        catalogue: Catalogue::new(scope),
+        limits: Arc::new(DaemonLimits::new(max_per_daemon)),
+        token,
+        sessions: Sessions::new(SESSION_IDLE_TIMEOUT, MAX_SESSIONS),
+        connections: Arc::new(ConnectionLimit {
+            active: AtomicUsize::new(0),
+            max: MAX_CONNECTIONS,
+        }),
+    });
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                let Some(slot) = server.connections.acquire() else {
+                    let _ = stream.set_write_timeout(Some(REQUEST_TIMEOUT));
+                    let _ = Response::text("503 Service Unavailable", "Too many connections")
+                        .with_header("Retry-After", "1".to_string())
+                        .write_to(&mut stream);
+                    continue;
+                };
+                let server = Arc::clone(&server);
+                thread::spawn(move || {
+                    let _slot = slot;
+                    if let Err(e) = server.handle_connection(stream) {
+                        eprintln!("{} Request failed: {:#}", "✗".red().bold(), e);
+                    }
+                });
+            }
+            Err(e) => eprintln!("{} Accept failed: {}", "✗".red().bold(), e),
+        }
+    }
+
+    Ok(())
+}
+
+impl Server {
+    /// Handle a single HTTP request.
+    fn handle_connection(&self, mut stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+        let request = match read_request(&stream) {
+            Ok(request) => request,
+            Err(response) => return Ok(response.write_to(&mut stream)?),
+        };
+
+        if request.path.split('?').next() != Some(ENDPOINT) {
+            return Ok(Response::text("404 Not Found", "Not found").write_to(&mut stream)?);
+        }
+        // Browsers send Origin; rejecting foreign ones blocks DNS rebinding
+        if request
+            .header("origin")
+            .is_some_and(|o| !is_local_origin(o))
+        {
+            return Ok(Response::text("403 Forbidden", "Origin not allowed").write_to(&mut stream)?);
+        }
+        if let Some(token) = &self.token {
+            if !bearer_matches(request.header("authorization"), token) {
+                return Ok(
+                    Response::text("401 Unauthorized", "Missing or invalid bearer token")
+                        .with_header("WWW-Authenticate", "Bearer".to_string())
+                        .write_to(&mut stream)?,
+                );
+            }
+        }
+
+        match request.method.as_str() {
+            "POST" => self.handle_post(&request, &mut stream),
+            "GET" => self.handle_get(&request, &mut stream),
+            "DELETE" => {
+                let removed = request
+                    .header("mcp-session-id")
+                    .is_some_and(|id| self.sessions.remove(id));
+                let response = if removed {
+                    Response::empty("204 No Content")
+                } else {
+                    Response::text("404 Not Found", "Session not found")
+                };
+                Ok(response.write_to(&mut stream)?)
+            }
+            _ => Ok(
+                Response::text("405 Method Not Allowed", "Method not allowed")
+                    .with_header("Allow", "GET, POST, DELETE".to_string())
+                    .write_to(&mut stream)?,
+            ),
+        }
+    }
+
+    /// Stream server-initiated notifications over SSE until the client
+    /// disconnects or the session ends.
+    fn handle_get(&self, request: &Request, stream: &mut TcpStream) -> Result<()> {
+        if !request
+            .header("accept")
+            .is_some_and(|accept| accept.contains("text/event-stream"))
+        {
+            return Ok(Response::text(
+                "406 Not Acceptable",
+                "Accept must include text/event-stream",
+            )
+            .write_to(stream)?);
+        }
+        let Some(id) = request.header("mcp-session-id") else {
+            return Ok(
+                Response::text("400 Bad Request", "Missing Mcp-Session-Id header")
+                    .write_to(stream)?,
+            );
+        };
+        let Some(session) = self.sessions.get(id) else {
+            return Ok(Response::text("404 Not Found", "Session not found").write_to(stream)?);
+        };
+
+        write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n")?;
+        stream.flush()?;
+
+        let mut seen = self.catalogue.generation();
+        loop {
+            let generation = self.catalogue.wait_for_change(seen, KEEPALIVE_INTERVAL);
+            // An open stream keeps its session alive
+            if self.sessions.get(id).is_none() {
+                return Ok(());
+            }
+
+            let sent = if generation == seen {
+                // Keeps proxies from timing out and notices a closed connection
+                write!(stream, ": keep-alive\n\n").and_then(|_| stream.flush())
+            } else if session.is_initialized() {
+                write_event(stream, &tools_list_changed())
+            } else {
+                Ok(())
+            };
+            if sent.is_err() {
+                return Ok(());
+            }
+            seen = generation;
+        }
+    }
+
+    /// Handle a POSTed JSON-RPC message or batch.
+    fn handle_post(&self, request: &Request, stream: &mut TcpStream) -> Result<()> {
+        if let Some(version) = request.header("mcp-protocol-version") {
+            if !PROTOCOL_VERSIONS.contains(&version) {
+                let message = format!("Unsupported MCP-Protocol-Version: {}", version);
+                return Ok(Response::text("400 Bad Request", &message).write_to(stream)?);
+            }
+        }
+
+        let (messages, is_batch) = match serde_json::from_slice(&request.body) {
+            Ok(serde_json::Value::Array(batch)) if !batch.is_empty() => (batch, true),
+            Ok(serde_json::Value::Array(_)) => {
+                let error = rpc_error(-32600, "Invalid Request: empty batch");
+                return Ok(Response::json("400 Bad Request", &error).write_to(stream)?);
+            }
+            Ok(message) => (vec![message], false),
+            Err(e) => {
+                let error = rpc_error(-32700, &format!("Parse error: {}", e));
+                return Ok(Response::json("400 Bad Request", &error).write_to(stream)?);
+            }
+        };
+
+        let has_requests = messages
+            .iter()
+            .any(|m| m.get("method").is_some() && m.get("id").is_some());
+        let initializes = messages.iter().any(|m| m["method"] == "initialize");
+
+        let (session, new_id) = match request.header("mcp-session-id") {
+            Some(id) => match self.sessions.get(id) {
+                Some(session) => (session, None),
+                None => {
+                    return Ok(
+                        Response::text("404 Not Found", "Session not found").write_to(stream)?
+                    )
+                }
+            },
+            None if initializes => (
+                Arc::new(Session::new(
+                    Arc::clone(&self.catalogue),
+                    Arc::clone(&self.limits),
+                )),
+                Some(new_session_id()?),
+            ),
+            None => {
+                return Ok(
+                    Response::text("400 Bad Request", "Missing Mcp-Session-Id header")
+                        .write_to(stream)?,
+                )
+            }
+        };
+
+        // 2025-06-18 removed batching again
+        if is_batch
+            && (session.negotiated("2025-06-18")
+                || request.header("mcp-protocol-version") == Some("2025-06-18"))
+        {
+            let error = rpc_error(
+                -32600,
+                "Invalid Request: batches are not supported in protocol version 2025-06-18",
+            );
+            return Ok(Response::json("400 Bad Request", &error).write_to(stream)?);
+        }
+
+        let mut messages = messages.into_iter();
+        let mut handle = |message| session.handle_message(message);
+
+        // Only notifications and responses: nothing to send back
+        if !has_requests {
+            messages.for_each(|message| {
+                handle(message);
+            });
+            return Ok(Response::empty("202 Accepted").write_to(stream)?);
+        }
+
+        // A new session is only kept once initialize succeeds, so handle its
+        // messages before choosing the headers
+        let mut replies = Vec::new();
+        if let Some(id) = &new_id {
+            replies.extend(messages.by_ref().filter_map(&mut handle));
+            if !session.is_initialized() {
+                let body = reply_body(replies, is_batch);
+                return Ok(Response::json("400 Bad Request", &body).write_to(stream)?);
+            }
+            if !self.sessions.insert(id.clone(), Arc::clone(&session)) {
+                return Ok(
+                    Response::text("503 Service Unavailable", "Too many sessions")
+                        .write_to(stream)?,
+                );
+            }
+        }
+
+        let accepts_sse = request
+            .header("accept")
+            .is_some_and(|accept| accept.contains("text/event-stream"));
+        if !accepts_sse {
+            replies.extend(messages.filter_map(handle));
+            // Everything was cancelled
+            if replies.is_empty() {
+                return Ok(Response::empty("202 Accepted").write_to(stream)?);
+            }
+            let mut response = Response::json("200 OK", &reply_body(replies, is_batch));
+            if let Some(id) = new_id {
+                response = response.with_header("Mcp-Session-Id", id);
+            }
+            return Ok(response.write_to(stream)?);
+        }
+
+        // Stream each reply as an SSE event as soon as it's ready
+        let mut head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n".to_string();
+        if let Some(id) = &new_id {
+            head.push_str(&format!("Mcp-Session-Id: {}\r\n", id));
+        }
+        write!(stream, "{}\r\n", head)?;
+        for reply in replies {
+            write_event(stream, &reply)?;
+        }
+        for message in messages {
+            if let Some(reply) = handle(message) {
+                write_event(stream, &reply)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a request line, headers and body, or the error response to send.
+fn read_request(stream: &TcpStream) -> std::result::Result<Request, Response> {
+    let bad_request = |_| Response::text("400 Bad Request", "Malformed request");
+    let mut reader = BufReader::new(stream.try_clone().map_err(bad_request)?);
+
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+
+    let mut headers = HashMap::new();
+    for count in 0.. {
+        let line = read_line(&mut reader)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if count == MAX_HEADERS {
+            return Err(Response::text(
+                "431 Request Header Fields Too Large",
+                "Too many headers",
+            ));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let mut body = Vec::new();
+    if method == "POST" {
+        let Some(length) = headers
+            .get("content-length")
+            .and_then(|l| l.parse::<usize>().ok())
+        else {
+            return Err(Response::text(
+                "411 Length Required",
+                "Content-Length required",
+            ));
+        };
+        if length > MAX_BODY_BYTES {
+            return Err(Response::text(
+                "413 Payload Too Large",
+                "Request body too large",
+            ));
+        }
+        body.resize(length, 0);
+        reader.read_exact(&mut body).map_err(bad_request)?;
+    }
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Read one line of at most `MAX_HEADER_LINE_BYTES`; empty at EOF.
+fn read_line(reader: &mut impl BufRead) -> std::result::Result<String, Response> {
+    let mut line = String::new();
+    reader
+        .take(MAX_HEADER_LINE_BYTES as u64 + 1)
+        .read_line(&mut line)
+        .map_err(|_| Response::text("400 Bad Request", "Malformed request"))?;
+    if line.len() > MAX_HEADER_LINE_BYTES {
+        return Err(Response::text(
+            "431 Request Header Fields Too Large",
+            "Header line too long",
+        ));
+    }
+    Ok(line)
+}
+
+fn write_event(stream: &mut TcpStream, message: &serde_json::Value) -> io::Result<()> {
+    write!(stream, "event: message\ndata: {}\n\n", message)?;
+    stream.flush()
+}
+
+/// Replies to a batch go back as an array, a single reply on its own.
+fn reply_body(mut replies: Vec<serde_json::Value>, is_batch: bool) -> serde_json::Value {
+    if is_batch || replies.len() != 1 {
+        serde_json::Value::Array(replies)
+    } else {
+        replies.remove(0)
+    }
+}
+
+fn rpc_error(code: i32, message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": {
+            "code": code,
+            "message": message
+        }
+    })
+}
+
+/// A random, unguessable session ID.
+fn new_session_id() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .context("Failed to read /dev/urandom")?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Whether an Origin header names this machine.
+fn is_local_origin(origin: &str) -> bool {
+    let host = origin
+        .split_once("://")
+        .map_or(origin, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or("");
+    let host = match host.strip_prefix('[') {
+        // [::1]:port
+        Some(rest) => rest.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+/// Compare a bearer token without short-circuiting on the first difference.
+fn bearer_matches(header: Option<&str>, token: &str) -> bool {
+    let Some(given) = header.and_then(|h| h.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_and_token_checks() {
+        assert!(is_local_origin("http://localhost:3000"));
+        assert!(is_local_origin("http://127.0.0.1"));
+        assert!(is_local_origin("http://[::1]:8080"));
+        assert!(!is_local_origin("https://evil.example"));
+        assert!(!is_local_origin("http://localhost.evil.example"));
+
+        assert!(bearer_matches(Some("Bearer s3cret"), "s3cret"));
+        assert!(!bearer_matches(Some("Bearer s3cre"), "s3cret"));
+        assert!(!bearer_matches(Some("s3cret"), "s3cret"));
+        assert!(!bearer_matches(None, "s3cret"));
+    }
+
+    /// Send `raw` to a local socket and read it back as a request.
+    fn parse_raw(raw: &str) -> std::result::Result<Request, Response> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(raw.as_bytes()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        read_request(&server)
+    }
+
+    #[test]
+    fn test_header_limits() {
+        let request = parse_raw("GET /mcp HTTP/1.1\r\nAccept: text/event-stream\r\n\r\n")
+            .ok()
+            .expect("request should parse");
+        assert_eq!(request.header("accept"), Some("text/event-stream"));
+
+        let many: String = (0..=MAX_HEADERS)
+            .map(|i| format!("X-{}: a\r\n", i))
+            .collect();
+        let error = parse_raw(&format!("GET /mcp HTTP/1.1\r\n{}\r\n", many))
+            .err()
+            .unwrap();
+        assert!(error.status.starts_with("431"));
+
+        let long = "a".repeat(MAX_HEADER_LINE_BYTES);
+        let error = parse_raw(&format!("GET /mcp HTTP/1.1\r\nX-Long: {}\r\n\r\n", long))
+            .err()
+            .unwrap();
+        assert!(error.status.starts_with("431"));
+    }
+
+    #[test]
+    fn test_idle_sessions_expire() {
+        let catalogue = Catalogue::new(Scope::new(Vec::new(), Vec::new()).unwrap());
+        let limits = Arc::new(DaemonLimits::new(1));
+        let session = || Arc::new(Session::new(Arc::clone(&catalogue), Arc::clone(&limits)));
+
+        let sessions = Sessions::new(Duration::from_millis(200), 1);
+        assert!(sessions.insert("a".to_string(), session()));
+        assert!(!sessions.insert("b".to_string(), session()));
+
+        // Lookups keep a session alive
+        thread::sleep(Duration::from_millis(120));
+        assert!(sessions.get("a").is_some());
+        thread::sleep(Duration::from_millis(120));
+        assert!(sessions.get("a").is_some());
+
+        thread::sleep(Duration::from_millis(250));
+        assert!(sessions.get("a").is_none());
+        assert!(sessions.insert("b".to_string(), session()));
+    }
+
+    #[test]
+    fn test_connection_limit() {
+        let limit = Arc::new(ConnectionLimit {
+            active: AtomicUsize::new(0),
+            max: 2,
+        });
+        let first = limit.acquire().unwrap();
+        let _second = limit.acquire().unwrap();
+        assert!(limit.acquire().is_none());
+
+        drop(first);
+        assert!(limit.acquire().is_some());
+    }
+}
@@ -0,0 +1,90 @@
+//! MCP prompts built from installed skills.
+//!
+//! Each installed skill with MCP (or core) instructions is offered as a
+//! prompt, so clients can pull a skill's guidance into a conversation.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+use super::mcp_bridge::Scope;
+use super::skill::installed_skill_manifests;
+use super::skill_validate::skill_file;
+
+/// An installed skill whose instructions are served as a prompt.
+struct SkillPrompt {
+    name: String,
+    description: String,
+    instructions: PathBuf,
+}
+
+/// Installed skills with instructions, limited to those using daemons in `scope`.
+fn skill_prompts(scope: &Scope) -> Vec<SkillPrompt> {
+    let mut prompts: Vec<SkillPrompt> = installed_skill_manifests()
+        .into_iter()
+        .filter_map(|(source, manifest)| {
+            let daemons: Vec<&str> = manifest.daemons.iter().map(|d| d.name.as_str()).collect();
+            if !scope.exposes_skill(&daemons) {
+                return None;
+            }
+
+            let instructions = manifest.instructions.as_ref()?;
+            let file = instructions.mcp.as_ref().or(instructions.core.as_ref())?;
+            Some(SkillPrompt {
+                instructions: skill_file(&source, file)?,
+                name: manifest.name,
+                description: manifest.description,
+            })
+        })
+        .collect();
+
+    prompts.sort_by(|a, b| a.name.cmp(&b.name));
+    prompts.dedup_by(|a, b| a.name == b.name);
+    prompts
+}
+
+/// Result of `prompts/list`.
+pub fn list(scope: &Scope) -> Value {
+    let prompts: Vec<Value> = skill_prompts(scope)
+        .iter()
+        .map(|p| {
+            json!({
+                "name": p.name,
+                "description": p.description,
+                "arguments": [{
+                    "name": "task",
+                    "description": "What you want to get done with this skill",
+                    "required": false
+                }]
+            })
+        })
+        .collect();
+
+    json!({ "prompts": prompts })
+}
+
+/// Result of `prompts/get`.
+pub fn get(scope: &Scope, params: &Value) -> Result<Value> {
+    let name = params["name"].as_str().context("Missing prompt name")?;
+    let Some(prompt) = skill_prompts(scope).into_iter().find(|p| p.name == name) else {
+        bail!("Unknown prompt: {}", name);
+    };
+
+    let mut text = fs::read_to_string(&prompt.instructions)
+        .with_context(|| format!("Failed to read {}", prompt.instructions.display()))?;
+    if let Some(task) = params["arguments"]["task"]
+        .as_str()
+        .filter(|t| !t.is_empty())
+    {
+        text.push_str(&format!("\n\n## Task\n\n{}\n", task));
+    }
+
+    Ok(json!({
+        "description": prompt.description,
+        "messages": [{
+            "role": "user",
+            "content": { "type": "text", "text": text }
+        }]
+    }))
+}
@@ -0,0 +1,74 @@
+//! MCP resources exposing daemon state.
+//!
+//! Every daemon in scope has three read-only resources:
+//! `fgp://<daemon>/health`, `fgp://<daemon>/manifest` and `fgp://<daemon>/logs`.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+
+use super::logs::recent_lines;
+use super::mcp_bridge::Scope;
+use super::methods::installed_manifest;
+use super::support_bundle::health_json;
+
+/// How many log lines the logs resource returns.
+const LOG_LINES: usize = 200;
+
+/// Resource kinds: path segment, MIME type, description.
+const KINDS: &[(&str, &str, &str)] = &[
+    ("health", "application/json", "Current health check result"),
+    ("manifest", "application/json", "Installed daemon manifest"),
+    ("logs", "text/plain", "Most recent daemon log lines"),
+];
+
+/// Result of `resources/list`.
+pub fn list(scope: &Scope) -> Value {
+    let resources: Vec<Value> = scope
+        .daemons()
+        .iter()
+        .flat_map(|daemon| {
+            KINDS.iter().map(move |(kind, mime_type, description)| {
+                json!({
+                    "uri": format!("fgp://{}/{}", daemon, kind),
+                    "name": format!("{} {}", daemon, kind),
+                    "description": description,
+                    "mimeType": mime_type
+                })
+            })
+        })
+        .collect();
+
+    json!({ "resources": resources })
+}
+
+/// Result of `resources/read`.
+pub fn read(scope: &Scope, params: &Value) -> Result<Value> {
+    let uri = params["uri"].as_str().context("Missing uri")?;
+    let Some((daemon, kind)) = uri
+        .strip_prefix("fgp://")
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(daemon, _)| scope.daemons().iter().any(|d| d == daemon))
+    else {
+        bail!("Resource not found: {}", uri);
+    };
+
+    let (mime_type, text) = match kind {
+        "health" => (
+            "application/json",
+            serde_json::to_string_pretty(&health_json(daemon))?,
+        ),
+        "manifest" => {
+            let path = installed_manifest(daemon);
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            ("application/json", content)
+        }
+        "logs" => ("text/plain", recent_lines(daemon, LOG_LINES)?.join("\n")),
+        _ => bail!("Resource not found: {}", uri),
+    };
+
+    Ok(json!({
+        "contents": [{ "uri": uri, "mimeType": mime_type, "text": text }]
+    }))
+}
@@ -0,0 +1,478 @@
+//! FGP workflows published as MCP tools.
+//!
+//! Workflows in `~/.fgp/workflows` and those shipped by installed skills are
+//! offered as `fgp_workflow__<name>` tools. Inputs a workflow declares under
+//! `variables:` become the tool's input schema, and their values replace
+//! `{{name}}` placeholders in step params before the workflow runs.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use super::mcp_bridge::{sanitize_tool_name, with_hash_suffix, Scope, MAX_TOOL_NAME_LEN};
+use super::skill::installed_skill_manifests;
+use super::skill_validate::skill_file;
+use super::workflow::workflows_dir;
+
+/// Prefix of every workflow tool name.
+pub const TOOL_PREFIX: &str = "fgp_workflow__";
+
+/// The parts of a workflow file needed to publish it.
+#[derive(Debug, Deserialize)]
+struct WorkflowFile {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    variables: BTreeMap<String, Variable>,
+    #[serde(default)]
+    steps: Vec<StepFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StepFile {
+    service: String,
+}
+
+/// A declared workflow input: a full spec, or just its default value.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Variable {
+    Spec(VariableSpec),
+    Default(Value),
+}
+
+/// Unknown keys are rejected so a map-valued default isn't taken for a spec.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VariableSpec {
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    default: Option<Value>,
+    #[serde(default)]
+    required: Option<bool>,
+}
+
+impl Variable {
+    fn default(&self) -> Option<&Value> {
+        match self {
+            Variable::Spec(spec) => spec.default.as_ref(),
+            Variable::Default(value) => Some(value),
+        }
+    }
+
+    /// Inputs without a default are required unless marked otherwise.
+    fn required(&self) -> bool {
+        match self {
+            Variable::Spec(spec) => spec.required.unwrap_or(spec.default.is_none()),
+            Variable::Default(_) => false,
+        }
+    }
+
+    fn schema(&self) -> Value {
+        let mut schema = json!({});
+        match self {
+            Variable::Spec(spec) => {
+                let kind = spec
+                    .kind
+                    .clone()
+                    .unwrap_or_else(|| json_type(self.default()).to_string());
+                schema["type"] = json!(kind);
+                if let Some(description) = &spec.description {
+                    schema["description"] = json!(description);
+                }
+            }
+            Variable::Default(value) => schema["type"] = json!(json_type(Some(value))),
+        }
+        if let Some(default) = self.default() {
+            schema["default"] = default.clone();
+        }
+        schema
+    }
+}
+
+/// JSON Schema type of a default value; strings when there is none.
+fn json_type(value: Option<&Value>) -> &'static str {
+    match value {
+        Some(Value::Bool(_)) => "boolean",
+        Some(Value::Number(n)) if n.is_f64() => "number",
+        Some(Value::Number(_)) => "integer",
+        Some(Value::Array(_)) => "array",
+        Some(Value::Object(_)) => "object",
+        _ => "string",
+    }
+}
+
+/// A workflow published as a tool.
+struct WorkflowTool {
+    tool_name: String,
+    name: String,
+    path: PathBuf,
+    description: Option<String>,
+    file: WorkflowFile,
+}
+
+impl WorkflowTool {
+    fn input_schema(&self) -> Value {
+        let properties: serde_json::Map<String, Value> = self
+            .file
+            .variables
+            .iter()
+            .map(|(name, variable)| (name.clone(), variable.schema()))
+            .collect();
+        let required: Vec<&String> = self
+            .file
+            .variables
+            .iter()
+            .filter(|(_, variable)| variable.required())
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut schema = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            schema["required"] = json!(required);
+        }
+        schema
+    }
+}
+
+/// Workflow files as (name, path, description override): user workflows
+/// first, then those shipped by installed skills as `<skill>-<workflow>`.
+fn sources() -> Vec<(String, PathBuf, Option<String>)> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(workflows_dir())
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    paths.retain(|p| p.extension().is_some_and(|e| e == "yaml" || e == "yml"));
+    paths.sort();
+
+    let mut sources: Vec<(String, PathBuf, Option<String>)> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some((name, path, None))
+        })
+        .collect();
+
+    let mut skill_sources = Vec::new();
+    for (source, manifest) in installed_skill_manifests() {
+        for (key, workflow) in manifest.workflows {
+            // Only files inside the skill's own directory
+            let Some(path) = skill_file(&source, &workflow.file) else {
+                continue;
+            };
+            skill_sources.push((
+                format!("{}-{}", manifest.name, key),
+                path,
+                workflow.description,
+            ));
+        }
+    }
+    skill_sources.sort_by(|a, b| a.0.cmp(&b.0));
+    sources.extend(skill_sources);
+    sources
+}
+
+/// MCP tool name for a workflow.
+fn encode_tool_name(name: &str) -> String {
+    let tool_name = format!("{}{}", TOOL_PREFIX, sanitize_tool_name(name));
+    if tool_name.len() > MAX_TOOL_NAME_LEN {
+        with_hash_suffix(&tool_name, "workflow", name)
+    } else {
+        tool_name
+    }
+}
+
+/// Readable workflows whose steps only use daemons in `scope`.
+///
+/// When two workflows map to the same tool name the first one wins, so user
+/// workflows shadow skill ones.
+fn workflow_tools(scope: &Scope) -> Vec<WorkflowTool> {
+    let mut seen = HashSet::new();
+    sources()
+        .into_iter()
+        .filter_map(|(name, path, description)| {
+            let content = fs::read_to_string(&path).ok()?;
+            let file: WorkflowFile = serde_yaml::from_str(&content).ok()?;
+            if !file.steps.iter().all(|step| scope.allows(&step.service)) {
+                return None;
+            }
+
+            let tool_name = encode_tool_name(&name);
+            if !seen.insert(tool_name.clone()) {
+                return None;
+            }
+            Some(WorkflowTool {
+                tool_name,
+                name,
+                path,
+                description,
+                file,
+            })
+        })
+        .collect()
+}
+
+/// Tool definitions for `tools/list`.
+pub fn tools(scope: &Scope) -> Vec<Value> {
+    workflow_tools(scope)
+        .into_iter()
+        .map(|tool| {
+            let description = tool
+                .description
+                .clone()
+                .or_else(|| tool.file.description.clone())
+                .unwrap_or_else(|| format!("Run the '{}' workflow", tool.name));
+            json!({
+                "name": tool.tool_name,
+                "description": description,
+                "inputSchema": tool.input_schema(),
+                "outputSchema": output_schema()
+            })
+        })
+        .collect()
+}
+
+/// Modification times of every workflow file, so the bridge notices edits.
+pub fn fingerprint() -> Vec<(PathBuf, Option<SystemTime>)> {
+    sources()
+        .into_iter()
+        .map(|(_, path, _)| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+/// Load the workflow behind `tool_name` with `arguments` filled in.
+pub fn prepare(
+    scope: &Scope,
+    tool_name: &str,
+    arguments: &Value,
+) -> Result<fgp_workflow::Workflow> {
+    let Some(tool) = workflow_tools(scope)
+        .into_iter()
+        .find(|t| t.tool_name == tool_name)
+    else {
+        bail!("Unknown tool: {}", tool_name);
+    };
+
+    let empty = serde_json::Map::new();
+    let arguments = match arguments {
+        Value::Object(arguments) => arguments,
+        Value::Null => &empty,
+        _ => bail!("Tool arguments must be an object"),
+    };
+    if let Some(unknown) = arguments
+        .keys()
+        .find(|k| !tool.file.variables.contains_key(*k))
+    {
+        bail!("Unknown argument: {}", unknown);
+    }
+
+    let mut values = BTreeMap::new();
+    for (name, variable) in &tool.file.variables {
+        match arguments.get(name).or(variable.default()) {
+            Some(value) => {
+                values.insert(name.clone(), value.clone());
+            }
+            None if variable.required() => bail!("Missing required argument: {}", name),
+            None => {}
+        }
+    }
+
+    let mut workflow = fgp_workflow::yaml::load_file(&tool.path)
+        .with_context(|| format!("Failed to load workflow {}", tool.path.display()))?;
+    for step in &mut workflow.steps {
+        substitute(&mut step.params, &values);
+    }
+    Ok(workflow)
+}
+
+/// Replace `{{name}}` placeholders with variable values.
+///
+/// A string that is only a placeholder takes the value as-is, keeping its
+/// JSON type; placeholders inside longer strings are replaced with text.
+/// Substituted values are never expanded again.
+fn substitute(value: &mut Value, values: &BTreeMap<String, Value>) {
+    match value {
+        Value::String(text) => {
+            let whole = placeholder(text).and_then(|name| values.get(name)).cloned();
+            *value = match whole {
+                Some(replacement) => replacement,
+                None => Value::String(substitute_text(text, values)),
+            };
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| substitute(v, values)),
+        Value::Object(map) => map.values_mut().for_each(|v| substitute(v, values)),
+        _ => {}
+    }
+}
+
+/// Replace placeholders in one left-to-right pass, leaving unknown ones as-is.
+fn substitute_text(text: &str, values: &BTreeMap<String, Value>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start + 2..].find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let end = start + 2 + len + 2;
+        match values.get(rest[start + 2..end - 2].trim()) {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(other) => out.push_str(&other.to_string()),
+            None => out.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The variable name if `text` is a single `{{name}}` placeholder.
+fn placeholder(text: &str) -> Option<&str> {
+    let name = text.trim().strip_prefix("{{")?.strip_suffix("}}")?.trim();
+    (!name.is_empty() && !name.contains(['{', '}'])).then_some(name)
+}
+
+/// Schema of [`result_json`].
+fn output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "workflow": { "type": "string" },
+            "result": {},
+            "steps": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "step": { "type": "integer" },
+                        "service": { "type": "string" },
+                        "method": { "type": "string" },
+                        "output": { "type": ["string", "null"] },
+                        "duration_ms": { "type": "number" }
+                    }
+                }
+            },
+            "total_ms": { "type": "number" }
+        },
+        "required": ["workflow", "result", "steps", "total_ms"]
+    })
+}
+
+/// Tool result payload: the final result plus per-step timing.
+pub fn result_json(workflow: &str, result: &fgp_workflow::WorkflowResult) -> Value {
+    let steps: Vec<Value> = result
+        .step_results
+        .iter()
+        .map(|step| {
+            json!({
+                "step": step.index + 1,
+                "service": step.step.service,
+                "method": step.step.method,
+                "output": step.step.output,
+                "duration_ms": step.duration_ms
+            })
+        })
+        .collect();
+
+    json!({
+        "workflow": workflow,
+        "result": result.result,
+        "steps": steps,
+        "total_ms": result.total_ms
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variables_schema_and_substitution() {
+        let file: WorkflowFile = serde_yaml::from_str(
+            r#"
+variables:
+  query:
+    type: string
+    description: Search query
+  limit: 10
+steps:
+  - service: gmail
+"#,
+        )
+        .unwrap();
+        let tool = WorkflowTool {
+            tool_name: encode_tool_name("inbox search"),
+            name: "inbox search".to_string(),
+            path: PathBuf::new(),
+            description: None,
+            file,
+        };
+        assert_eq!(tool.tool_name, "fgp_workflow__inbox_search");
+
+        let schema = tool.input_schema();
+        assert_eq!(schema["properties"]["query"]["type"], "string");
+        assert_eq!(schema["properties"]["limit"]["type"], "integer");
+        assert_eq!(schema["properties"]["limit"]["default"], 10);
+        assert_eq!(schema["required"], json!(["query"]));
+
+        let values = BTreeMap::from([
+            ("query".to_string(), json!("is:unread")),
+            ("limit".to_string(), json!(5)),
+        ]);
+        let mut params = json!({
+            "q": "{{ query }}",
+            "limit": "{{limit}}",
+            "label": "from {{query}} (max {{ limit }})",
+            "other": "{{unknown}}"
+        });
+        substitute(&mut params, &values);
+        assert_eq!(
+            params,
+            json!({
+                "q": "is:unread",
+                "limit": 5,
+                "label": "from is:unread (max 5)",
+                "other": "{{unknown}}"
+            })
+        );
+    }
+
+    #[test]
+    fn test_object_default_and_no_reexpansion() {
+        let file: WorkflowFile = serde_yaml::from_str(
+            r#"
+variables:
+  headers:
+    Accept: application/json
+  query:
+    type: string
+"#,
+        )
+        .unwrap();
+        let headers = &file.variables["headers"];
+        assert!(!headers.required());
+        assert_eq!(headers.schema()["type"], "object");
+        assert_eq!(
+            headers.schema()["default"],
+            json!({ "Accept": "application/json" })
+        );
+
+        let values = BTreeMap::from([
+            ("a".to_string(), json!("{{b}}")),
+            ("b".to_string(), json!("secret")),
+        ]);
+        let mut params = json!({ "text": "a={{a}}, b={{ b }}, {{unclosed" });
+        substitute(&mut params, &values);
+        assert_eq!(params["text"], "a={{b}}, b=secret, {{unclosed");
+    }
+}
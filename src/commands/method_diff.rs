@@ -0,0 +1,428 @@
+//! Compare the method surface of two daemon versions.
+//!
+//! Changes are classified as breaking (removed method, removed or newly
+//! required param, type change) or compatible (added method, added optional
+//! param), and a semver bump is suggested.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::methods::{self, MethodSpec, BUILTIN_METHODS};
+use super::skill::skills_dir;
+
+/// Whether a change can break existing callers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Impact {
+    Breaking,
+    Compatible,
+}
+
+#[derive(Debug, PartialEq)]
+struct Change {
+    impact: Impact,
+    description: String,
+}
+
+impl Change {
+    fn breaking(description: String) -> Self {
+        Self {
+            impact: Impact::Breaking,
+            description,
+        }
+    }
+
+    fn compatible(description: String) -> Self {
+        Self {
+            impact: Impact::Compatible,
+            description,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    fn label(self) -> &'static str {
+        match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+/// One side of the comparison.
+struct Surface {
+    label: String,
+    version: Option<String>,
+    methods: Vec<MethodSpec>,
+}
+
+/// Compare two method surfaces (`fgp methods diff <old> <new>`).
+///
+/// Each side is a manifest path, a package directory, an installed service,
+/// or `service@version` from the installed manifest or the skill cache.
+pub fn run(old: &str, new: &str) -> Result<()> {
+    let old = resolve(old)?;
+    let new = resolve(new)?;
+
+    println!(
+        "Comparing {} → {}",
+        describe(&old).bold(),
+        describe(&new).bold()
+    );
+    println!();
+
+    let changes = classify(&old.methods, &new.methods);
+    let (breaking, compatible): (Vec<&Change>, Vec<&Change>) =
+        changes.iter().partition(|c| c.impact == Impact::Breaking);
+
+    if changes.is_empty() {
+        println!("  {} No method surface changes", "✓".green().bold());
+    }
+    if !breaking.is_empty() {
+        println!("{}", "Breaking changes:".red().bold());
+        for change in &breaking {
+            println!("  {} {}", "✗".red(), change.description);
+        }
+        println!();
+    }
+    if !compatible.is_empty() {
+        println!("{}", "Compatible changes:".green().bold());
+        for change in &compatible {
+            println!("  {} {}", "+".green(), change.description);
+        }
+        println!();
+    }
+
+    let bump = suggest_bump(&changes);
+    let next = old.version.as_deref().and_then(|v| bump_version(v, bump));
+    match (&old.version, &next) {
+        (Some(from), Some(to)) => println!(
+            "Suggested version bump: {} ({} → {})",
+            bump.label().bold(),
+            from,
+            to.bold()
+        ),
+        _ => println!("Suggested version bump: {}", bump.label().bold()),
+    }
+
+    // Warn when the new side already declares a version that's too low
+    if let (Some(declared), Some(needed)) = (&new.version, &next) {
+        if let (Some(d), Some(n)) = (parse_version(declared), parse_version(needed)) {
+            if d < n {
+                println!(
+                    "  {} {} declares {}, lower than the suggested {}",
+                    "!".yellow().bold(),
+                    new.label,
+                    declared,
+                    needed
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn describe(surface: &Surface) -> String {
+    match &surface.version {
+        Some(v) if !surface.label.ends_with(&format!("@{}", v)) => {
+            format!("{} ({})", surface.label, v)
+        }
+        _ => surface.label.clone(),
+    }
+}
+
+/// Classify the changes from `old` to `new`.
+fn classify(old: &[MethodSpec], new: &[MethodSpec]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let is_builtin = |m: &MethodSpec| BUILTIN_METHODS.contains(&m.name.as_str());
+
+    for before in old.iter().filter(|m| !is_builtin(m)) {
+        let Some(after) = new.iter().find(|m| m.name == before.name) else {
+            changes.push(Change::breaking(format!("removed method {}", before.name)));
+            continue;
+        };
+
+        for param in &before.params {
+            let Some(now) = after.params.iter().find(|p| p.name == param.name) else {
+                changes.push(Change::breaking(format!(
+                    "{}: removed param '{}'",
+                    before.name, param.name
+                )));
+                continue;
+            };
+
+            if !methods::same_type(&param.param_type, &now.param_type) {
+                changes.push(Change::breaking(format!(
+                    "{}: param '{}' changed type from {} to {}",
+                    before.name, param.name, param.param_type, now.param_type
+                )));
+            }
+
+            match (param.required, now.required) {
+                (false, true) => changes.push(Change::breaking(format!(
+                    "{}: param '{}' is now required",
+                    before.name, param.name
+                ))),
+                (true, false) => changes.push(Change::compatible(format!(
+                    "{}: param '{}' is now optional",
+                    before.name, param.name
+                ))),
+                _ => {}
+            }
+        }
+
+        for param in &after.params {
+            if before.params.iter().any(|p| p.name == param.name) {
+                continue;
+            }
+            if param.required {
+                changes.push(Change::breaking(format!(
+                    "{}: added required param '{}'",
+                    before.name, param.name
+                )));
+            } else {
+                changes.push(Change::compatible(format!(
+                    "{}: added optional param '{}'",
+                    before.name, param.name
+                )));
+            }
+        }
+    }
+
+    for after in new.iter().filter(|m| !is_builtin(m)) {
+        if !old.iter().any(|m| m.name == after.name) {
+            changes.push(Change::compatible(format!("added method {}", after.name)));
+        }
+    }
+
+    changes
+}
+
+fn suggest_bump(changes: &[Change]) -> Bump {
+    changes
+        .iter()
+        .map(|c| match c.impact {
+            Impact::Breaking => Bump::Major,
+            Impact::Compatible => Bump::Minor,
+        })
+        .max()
+        .unwrap_or(Bump::Patch)
+}
+
+/// Parse `major.minor.patch`, ignoring a leading `v` and pre-release/build suffixes.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// Apply a bump. Before 1.0 each level shifts down one (as Cargo does): breaking
+/// changes bump the minor version and additions bump the patch version.
+fn bump_version(version: &str, bump: Bump) -> Option<String> {
+    let (major, minor, patch) = parse_version(version)?;
+    let next = match (bump, major) {
+        (Bump::Major, 0) => (0, minor + 1, 0),
+        (Bump::Major, _) => (major + 1, 0, 0),
+        (Bump::Minor, 0) => (0, minor, patch + 1),
+        (Bump::Minor, _) => (major, minor + 1, 0),
+        (Bump::Patch, _) => (major, minor, patch + 1),
+    };
+    Some(format!("{}.{}.{}", next.0, next.1, next.2))
+}
+
+/// Resolve a manifest path, package directory, `service` or `service@version`.
+fn resolve(spec: &str) -> Result<Surface> {
+    let path = Path::new(spec);
+    if path.exists() {
+        let manifest = if path.is_dir() {
+            find_manifest(path)
+                .with_context(|| format!("No manifest.json or skill.json in {}", path.display()))?
+        } else {
+            path.to_path_buf()
+        };
+        return load_surface(spec.to_string(), &manifest);
+    }
+
+    let (service, version) = match spec.split_once('@') {
+        Some((service, version)) => (service, Some(version)),
+        None => (spec, None),
+    };
+
+    let installed = methods::installed_manifest(service);
+    if installed.exists() {
+        let surface = load_surface(spec.to_string(), &installed)?;
+        if version.is_none() || surface.version.as_deref() == version {
+            return Ok(surface);
+        }
+    }
+
+    let Some(version) = version else {
+        bail!(
+            "'{}' is neither a manifest path nor an installed service",
+            spec
+        );
+    };
+
+    let cached = cached_versions(service);
+    if let Some((_, dir)) = cached.iter().find(|(v, _)| v == version) {
+        if let Some(manifest) = find_manifest(dir) {
+            return load_surface(spec.to_string(), &manifest);
+        }
+    }
+
+    let mut available: Vec<&str> = cached.iter().map(|(v, _)| v.as_str()).collect();
+    available.sort();
+    available.dedup();
+    if available.is_empty() {
+        bail!("No version {} of '{}' found", version, service);
+    }
+    bail!(
+        "No version {} of '{}' found. Available: {}",
+        version,
+        service,
+        available.join(", ")
+    )
+}
+
+fn load_surface(label: String, manifest_path: &Path) -> Result<Surface> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let manifest: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", manifest_path.display()))?;
+
+    Ok(Surface {
+        label,
+        version: manifest["version"].as_str().map(String::from),
+        methods: methods::parse_methods(&manifest["methods"]),
+    })
+}
+
+/// Find a manifest in a package, install or cache directory.
+fn find_manifest(dir: &Path) -> Option<PathBuf> {
+    [
+        "manifest.json",
+        ".fgp/skill.json",
+        "skill.json",
+        "source/manifest.json",
+        "source/.fgp/skill.json",
+    ]
+    .iter()
+    .map(|candidate| dir.join(candidate))
+    .find(|p| p.is_file())
+}
+
+/// Versions of a service in the skill cache (`cache/<marketplace>/<skill>/<version>`).
+fn cached_versions(service: &str) -> Vec<(String, PathBuf)> {
+    let names = [service.to_string(), format!("{}-gateway", service)];
+    let Ok(marketplaces) = fs::read_dir(skills_dir().join("cache")) else {
+        return Vec::new();
+    };
+
+    marketplaces
+        .flatten()
+        .flat_map(|marketplace| names.iter().map(move |n| marketplace.path().join(n)))
+        .filter_map(|skill_dir| fs::read_dir(skill_dir).ok())
+        .flat_map(|versions| versions.flatten())
+        .filter_map(|v| Some((v.file_name().to_str()?.to_string(), v.path())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_classify_and_bump() {
+        let old = methods::parse_methods(&json!([
+            {"name": "a.send", "params": [
+                {"name": "to", "type": "string", "required": true},
+                {"name": "cc", "type": "array"},
+                {"name": "limit", "type": "int"}
+            ]},
+            {"name": "a.old"}
+        ]));
+        let new = methods::parse_methods(&json!([
+            {"name": "a.send", "params": [
+                {"name": "to", "type": "string", "required": true},
+                {"name": "limit", "type": "integer", "required": true},
+                {"name": "bcc", "type": "array"}
+            ]},
+            {"name": "a.new"}
+        ]));
+
+        let changes = classify(&old, &new);
+        let breaking: Vec<&str> = changes
+            .iter()
+            .filter(|c| c.impact == Impact::Breaking)
+            .map(|c| c.description.as_str())
+            .collect();
+        assert_eq!(
+            breaking,
+            vec![
+                "a.send: removed param 'cc'",
+                "a.send: param 'limit' is now required",
+                "removed method a.old",
+            ]
+        );
+        assert_eq!(changes.len(), 5);
+
+        assert_eq!(suggest_bump(&changes), Bump::Major);
+        assert_eq!(bump_version("1.4.2", Bump::Major).as_deref(), Some("2.0.0"));
+        assert_eq!(bump_version("0.3.1", Bump::Major).as_deref(), Some("0.4.0"));
+        assert_eq!(
+            bump_version("v1.4.2", Bump::Minor).as_deref(),
+            Some("1.5.0")
+        );
+        assert_eq!(suggest_bump(&[]), Bump::Patch);
+    }
+
+    #[test]
+    fn test_generics_and_any_are_not_type_changes() {
+        let old = methods::parse_methods(&json!([
+            {"name": "a.list", "params": [
+                {"name": "labels", "type": "array"},
+                {"name": "limit", "type": "integer"},
+                {"name": "query", "type": "string"},
+                {"name": "filter", "type": "any"},
+                {"name": "cursor", "type": "string"}
+            ]}
+        ]));
+        let new = methods::parse_methods(&json!([
+            {"name": "a.list", "params": [
+                {"name": "labels", "type": "Vec<String>"},
+                {"name": "limit", "type": "Option<u32>"},
+                {"name": "query", "type": "Vec<String>"},
+                {"name": "filter", "type": "object"},
+                {"name": "cursor", "type": "any"}
+            ]}
+        ]));
+
+        let descriptions: Vec<String> = classify(&old, &new)
+            .into_iter()
+            .map(|c| c.description)
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec!["a.list: param 'query' changed type from string to Vec<String>"]
+        );
+    }
+}
@@ -1,10 +1,19 @@
 //! List available methods for a service.
+//!
+//! Methods come from the running daemon, or from the installed manifest when
+//! the daemon is down.
 
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
 use tabled::{Table, Tabled};
 
-use super::service_socket_path;
+use super::{fgp_services_dir, service_socket_path};
+
+/// Methods every daemon provides, which manifests don't list.
+pub const BUILTIN_METHODS: &[&str] = &["health", "methods", "stop"];
 
 #[derive(Tabled)]
 struct MethodInfo {
@@ -14,17 +23,255 @@ struct MethodInfo {
     description: String,
 }
 
-pub fn run(service: &str) -> Result<()> {
-    let socket_path = service_socket_path(service);
+/// A method as described by a daemon or a manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodSpec {
+    pub name: String,
+    pub description: Option<String>,
+    pub params: Vec<ParamSpec>,
+}
 
-    if !socket_path.exists() {
-        bail!(
-            "Service '{}' is not running. Run 'fgp start {}' first.",
-            service,
-            service
-        );
+/// A method parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    pub name: String,
+    pub param_type: String,
+    pub required: bool,
+    pub default: Option<Value>,
+    pub description: Option<String>,
+}
+
+/// Where a method list came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodSource {
+    Daemon,
+    Manifest,
+}
+
+/// Parse a `methods` array from a daemon response or manifest.
+///
+/// Params may be a list of `{name, type, required}` objects, a map of name to
+/// `{type, required}` (skill.json style), or a JSON Schema object.
+pub fn parse_methods(methods: &Value) -> Vec<MethodSpec> {
+    methods
+        .as_array()
+        .map(|items| items.iter().filter_map(parse_method).collect())
+        .unwrap_or_default()
+}
+
+fn parse_method(method: &Value) -> Option<MethodSpec> {
+    Some(MethodSpec {
+        name: method["name"].as_str()?.to_string(),
+        description: non_empty(&method["description"]),
+        params: parse_params(&method["params"]),
+    })
+}
+
+/// Parse a method's `params`: an FGP param list, a JSON Schema object, or a
+/// map of param name to definition.
+fn parse_params(params: &Value) -> Vec<ParamSpec> {
+    match params {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|p| {
+                let name = p["name"].as_str()?;
+                Some(parse_param(
+                    name,
+                    p,
+                    p["required"].as_bool().unwrap_or(false),
+                ))
+            })
+            .collect(),
+        // JSON Schema: {"type": "object", "properties": {...}, "required": [...]}
+        Value::Object(obj) if obj.contains_key("properties") => {
+            let required: Vec<&str> = obj
+                .get("required")
+                .and_then(|r| r.as_array())
+                .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            obj["properties"]
+                .as_object()
+                .map(|props| {
+                    props
+                        .iter()
+                        .map(|(name, p)| parse_param(name, p, required.contains(&name.as_str())))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        Value::Object(obj) => obj
+            .iter()
+            .map(|(name, p)| parse_param(name, p, p["required"].as_bool().unwrap_or(false)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn parse_param(name: &str, param: &Value, required: bool) -> ParamSpec {
+    let param_type = param["type"]
+        .as_str()
+        .or_else(|| param["param_type"].as_str())
+        .unwrap_or("any")
+        .to_string();
+
+    ParamSpec {
+        name: name.to_string(),
+        param_type,
+        required,
+        default: param.get("default").filter(|d| !d.is_null()).cloned(),
+        description: non_empty(&param["description"]),
+    }
+}
+
+/// JSON Schema for a method's `params`, as MCP `inputSchema` expects.
+///
+/// Params that already are a JSON Schema object pass through unchanged;
+/// other shapes are converted with [`params_schema`].
+pub fn input_schema(params: &Value) -> Value {
+    if params["type"] == "object" {
+        return params.clone();
+    }
+    params_schema(&parse_params(params))
+}
+
+/// MCP `outputSchema` for a method that declares what it returns, as a JSON
+/// Schema or type name under `returns` (or `return_type`).
+///
+/// MCP wants an object schema, so other return types are described as
+/// `{"result": ...}`.
+pub fn output_schema(method: &Value) -> Option<Value> {
+    let schema = match method
+        .get("returns")
+        .or_else(|| method.get("return_type"))?
+    {
+        Value::String(return_type) => type_schema(return_type),
+        declared @ Value::Object(_) => declared.clone(),
+        _ => return None,
+    };
+
+    if schema["type"] == "object" {
+        return Some(schema);
+    }
+    Some(json!({
+        "type": "object",
+        "properties": { "result": schema },
+        "required": ["result"]
+    }))
+}
+
+/// JSON Schema object describing `params`.
+pub fn params_schema(params: &[ParamSpec]) -> Value {
+    let mut properties = serde_json::Map::new();
+    for param in params {
+        let mut schema = type_schema(&param.param_type);
+        if let Some(description) = &param.description {
+            schema["description"] = json!(description);
+        }
+        if let Some(default) = &param.default {
+            schema["default"] = default.clone();
+        }
+        properties.insert(param.name.clone(), schema);
+    }
+
+    let required: Vec<&str> = params
+        .iter()
+        .filter(|p| p.required)
+        .map(|p| p.name.as_str())
+        .collect();
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required
+    })
+}
+
+/// JSON Schema for an FGP param type. Types we don't recognise accept anything.
+fn type_schema(param_type: &str) -> Value {
+    let param_type = param_type.trim();
+
+    if let Some(inner) = generic_arg(param_type, "option") {
+        return type_schema(inner);
+    }
+    if let Some(inner) = array_item_type(param_type) {
+        return json!({ "type": "array", "items": type_schema(inner) });
+    }
+
+    match normalize_type(param_type).as_str() {
+        t @ ("string" | "integer" | "number" | "boolean" | "array" | "object" | "null") => {
+            json!({ "type": t })
+        }
+        _ => json!({}),
+    }
+}
+
+/// Item type of Rust-style and suffix array spellings: Vec<T>, array<T>,
+/// list<T>, [T] and T[].
+fn array_item_type(param_type: &str) -> Option<&str> {
+    generic_arg(param_type, "vec")
+        .or_else(|| generic_arg(param_type, "array"))
+        .or_else(|| generic_arg(param_type, "list"))
+        .or_else(|| param_type.strip_suffix("[]"))
+        .or_else(|| param_type.strip_prefix('[')?.strip_suffix(']'))
+        .map(str::trim)
+}
+
+/// `T` from `name<T>`, ignoring case.
+fn generic_arg<'a>(param_type: &'a str, name: &str) -> Option<&'a str> {
+    let (outer, rest) = param_type.split_once('<')?;
+    if !outer.trim().eq_ignore_ascii_case(name) {
+        return None;
+    }
+    rest.strip_suffix('>').map(str::trim)
+}
+
+/// Canonical name for a param type, so "int" and "integer" compare equal.
+///
+/// `Option<T>` normalizes like `T` (optionality is tracked by `required`), and
+/// every array spelling becomes "array".
+pub fn normalize_type(param_type: &str) -> String {
+    let param_type = param_type.trim();
+    if let Some(inner) = generic_arg(param_type, "option") {
+        return normalize_type(inner);
     }
+    if array_item_type(param_type).is_some() {
+        return "array".to_string();
+    }
+
+    match param_type.to_ascii_lowercase().as_str() {
+        "str" | "text" => "string".to_string(),
+        "int" | "i32" | "i64" | "u32" | "u64" | "usize" => "integer".to_string(),
+        "float" | "f32" | "f64" | "double" => "number".to_string(),
+        "bool" => "boolean".to_string(),
+        "list" | "vec" => "array".to_string(),
+        "map" | "dict" => "object".to_string(),
+        other => other.to_string(),
+    }
+}
 
+/// Whether two param types agree once normalized. "any" means the side
+/// doesn't say, so it matches every type.
+pub fn same_type(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_type(a), normalize_type(b));
+    a == b || a == "any" || b == "any"
+}
+
+fn non_empty(value: &Value) -> Option<String> {
+    value.as_str().filter(|s| !s.is_empty()).map(String::from)
+}
+
+/// Read the methods declared in a manifest file.
+pub fn manifest_methods(path: &Path) -> Result<Vec<MethodSpec>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let manifest: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(parse_methods(&manifest["methods"]))
+}
+
+/// Fetch methods from a running daemon.
+pub fn daemon_methods(service: &str) -> Result<Vec<MethodSpec>> {
+    let socket_path = service_socket_path(service);
     let client = fgp_daemon::FgpClient::new(&socket_path).context("Failed to connect to daemon")?;
 
     let response = client.methods().context("Failed to get methods")?;
@@ -35,25 +282,218 @@ pub fn run(service: &str) -> Result<()> {
     }
 
     let result = response.result.unwrap_or_default();
-    let methods_array = result["methods"].as_array().cloned().unwrap_or_default();
+    Ok(parse_methods(&result["methods"]))
+}
+
+/// Get a service's methods from the daemon, or its manifest if it isn't running.
+pub fn load(service: &str) -> Result<(Vec<MethodSpec>, MethodSource)> {
+    if service_socket_path(service).exists() {
+        match daemon_methods(service) {
+            Ok(methods) => return Ok((methods, MethodSource::Daemon)),
+            Err(e) if !installed_manifest(service).exists() => return Err(e),
+            // Stale socket; fall back to the manifest
+            Err(_) => {}
+        }
+    }
+
+    let manifest_path = installed_manifest(service);
+    if !manifest_path.exists() {
+        bail!(
+            "Service '{}' is not running. Run 'fgp start {}' first.",
+            service,
+            service
+        );
+    }
 
-    println!("{} methods:", service.bold());
+    Ok((manifest_methods(&manifest_path)?, MethodSource::Manifest))
+}
+
+/// Path of a service's installed manifest.
+pub fn installed_manifest(service: &str) -> PathBuf {
+    fgp_services_dir().join(service).join("manifest.json")
+}
+
+pub fn run(service: &str, schema: bool) -> Result<()> {
+    let (methods, source) = load(service)?;
+
+    match source {
+        MethodSource::Daemon => println!("{} methods:", service.bold()),
+        MethodSource::Manifest => println!(
+            "{} methods {}:",
+            service.bold(),
+            "(from manifest; daemon not running)".yellow()
+        ),
+    }
     println!();
 
-    let methods: Vec<MethodInfo> = methods_array
+    if methods.is_empty() {
+        println!("  No methods available.");
+        return Ok(());
+    }
+
+    if schema {
+        print_schema(&methods);
+        return Ok(());
+    }
+
+    let rows: Vec<MethodInfo> = methods
         .iter()
         .map(|m| MethodInfo {
-            name: m["name"].as_str().unwrap_or("?").to_string(),
-            description: m["description"].as_str().unwrap_or("").to_string(),
+            name: m.name.clone(),
+            description: m.description.clone().unwrap_or_default(),
         })
         .collect();
 
-    if methods.is_empty() {
-        println!("  No methods available.");
-    } else {
-        let table = Table::new(&methods).to_string();
-        println!("{}", table);
-    }
+    let table = Table::new(&rows).to_string();
+    println!("{}", table);
 
     Ok(())
 }
+
+/// Print each method with its full parameter definitions.
+fn print_schema(methods: &[MethodSpec]) {
+    for method in methods {
+        match &method.description {
+            Some(description) => println!("{}  {}", method.name.cyan().bold(), description),
+            None => println!("{}", method.name.cyan().bold()),
+        }
+
+        if method.params.is_empty() {
+            println!("  {}", "(no params)".dimmed());
+        }
+
+        let width = method
+            .params
+            .iter()
+            .map(|p| p.name.len())
+            .max()
+            .unwrap_or(0);
+        for param in &method.params {
+            let required = if param.required {
+                "required".red().to_string()
+            } else {
+                "optional".dimmed().to_string()
+            };
+
+            let mut line = format!(
+                "  {:<width$}  {:<8}  {}",
+                param.name,
+                param.param_type,
+                required,
+                width = width
+            );
+            if let Some(default) = &param.default {
+                line.push_str(&format!("  default: {}", default));
+            }
+            if let Some(description) = &param.description {
+                line.push_str(&format!("  {}", description.dimmed()));
+            }
+            println!("{}", line);
+        }
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_methods_param_shapes() {
+        let methods = parse_methods(&json!([
+            {
+                "name": "gmail.send",
+                "description": "Send an email",
+                "params": [
+                    {"name": "to", "type": "string", "required": true},
+                    {"name": "cc", "param_type": "array", "required": false, "default": null}
+                ]
+            },
+            {
+                "name": "gmail.search",
+                "params": {
+                    "type": "object",
+                    "properties": {"query": {"type": "string", "description": "Search query"}},
+                    "required": ["query"]
+                }
+            },
+            {"name": "gmail.labels", "params": {"limit": {"type": "integer", "default": 10}}}
+        ]));
+
+        assert_eq!(methods.len(), 3);
+        assert_eq!(methods[0].params[0].param_type, "string");
+        assert!(methods[0].params[0].required);
+        assert_eq!(methods[0].params[1].param_type, "array");
+        assert_eq!(methods[0].params[1].default, None);
+
+        assert_eq!(methods[1].description, None);
+        assert!(methods[1].params[0].required);
+        assert_eq!(
+            methods[1].params[0].description.as_deref(),
+            Some("Search query")
+        );
+
+        assert!(!methods[2].params[0].required);
+        assert_eq!(methods[2].params[0].default, Some(json!(10)));
+    }
+
+    #[test]
+    fn test_normalize_type_sees_through_generics() {
+        assert_eq!(normalize_type("Option<u32>"), "integer");
+        assert_eq!(normalize_type("Vec<String>"), "array");
+        assert_eq!(normalize_type("[String]"), "array");
+        assert_eq!(normalize_type("string[]"), "array");
+        assert_eq!(normalize_type("Option<Vec<u64>>"), "array");
+        assert_eq!(normalize_type("String"), "string");
+    }
+
+    #[test]
+    fn test_input_schema_from_fgp_params() {
+        let schema = input_schema(&json!([
+            {"name": "to", "param_type": "String", "required": true, "description": "Recipient"},
+            {"name": "cc", "param_type": "Vec<String>", "required": false},
+            {"name": "limit", "param_type": "Option<u32>", "required": false, "default": 10},
+            {"name": "extra", "param_type": "any", "required": false}
+        ]));
+
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "properties": {
+                    "to": {"type": "string", "description": "Recipient"},
+                    "cc": {"type": "array", "items": {"type": "string"}},
+                    "limit": {"type": "integer", "default": 10},
+                    "extra": {}
+                },
+                "required": ["to"]
+            })
+        );
+
+        // Daemons that already send JSON Schema keep it as-is
+        let given =
+            json!({"type": "object", "properties": {"q": {"type": "string", "enum": ["a"]}}});
+        assert_eq!(input_schema(&given), given);
+        assert_eq!(
+            input_schema(&Value::Null),
+            json!({"type": "object", "properties": {}, "required": []})
+        );
+    }
+
+    #[test]
+    fn test_output_schema() {
+        assert_eq!(output_schema(&json!({"name": "a"})), None);
+        assert_eq!(
+            output_schema(&json!({"returns": {"type": "object", "properties": {}}})),
+            Some(json!({"type": "object", "properties": {}}))
+        );
+        assert_eq!(
+            output_schema(&json!({"return_type": "Vec<String>"})),
+            Some(json!({
+                "type": "object",
+                "properties": {"result": {"type": "array", "items": {"type": "string"}}},
+                "required": ["result"]
+            }))
+        );
+    }
+}
@@ -0,0 +1,505 @@
+//! Prometheus/OpenMetrics exporter for FGP daemons.
+//!
+//! `fgp metrics dump` prints the current metrics once; `fgp metrics serve`
+//! exposes them on `/metrics` for Prometheus to scrape.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use super::monitor::restart_counts;
+use super::{fgp_services_dir, service_socket_path};
+
+/// Health statuses exported as an enum-style gauge.
+const KNOWN_STATUSES: &[&str] = &["healthy", "degraded", "unhealthy", "stopped", "error"];
+
+/// Timeout for reading a scrape request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A single service's scraped state.
+struct ServiceSample {
+    name: String,
+    up: bool,
+    status: String,
+    version: Option<String>,
+    latency_secs: Option<f64>,
+    uptime_secs: Option<u64>,
+    pid: Option<u32>,
+}
+
+/// Collects metrics, keeping process state between scrapes so CPU usage
+/// reflects the interval since the previous scrape.
+struct Collector {
+    system: System,
+    primed: bool,
+}
+
+impl Collector {
+    fn new() -> Self {
+        Self {
+            system: System::new(),
+            primed: false,
+        }
+    }
+
+    /// Scrape every installed service and render Prometheus text format.
+    fn collect(&mut self) -> String {
+        let samples = sample_services();
+
+        let pids: Vec<Pid> = samples
+            .iter()
+            .filter_map(|s| s.pid)
+            .map(Pid::from_u32)
+            .collect();
+        self.refresh_processes(&pids);
+
+        render(&samples, &restart_counts(), &self.system)
+    }
+
+    /// Refresh CPU and memory for the given processes.
+    ///
+    /// CPU usage needs two samples; on the first call we take both.
+    fn refresh_processes(&mut self, pids: &[Pid]) {
+        if pids.is_empty() {
+            return;
+        }
+
+        let kind = ProcessRefreshKind::new().with_cpu().with_memory();
+        self.system
+            .refresh_processes_specifics(ProcessesToUpdate::Some(pids), true, kind);
+
+        if !self.primed {
+            std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+            self.system
+                .refresh_processes_specifics(ProcessesToUpdate::Some(pids), true, kind);
+            self.primed = true;
+        }
+    }
+}
+
+/// Print metrics once in Prometheus text format.
+pub fn dump() -> Result<()> {
+    let mut collector = Collector::new();
+    print!("{}", collector.collect());
+    Ok(())
+}
+
+/// Serve metrics over HTTP on `listen` (e.g. "127.0.0.1:9464").
+pub fn serve(listen: &str) -> Result<()> {
+    let listener =
+        TcpListener::bind(listen).with_context(|| format!("Failed to listen on {}", listen))?;
+
+    println!(
+        "{} Serving FGP metrics on http://{}/metrics (Ctrl+C to stop)",
+        "→".blue().bold(),
+        listen
+    );
+
+    let mut collector = Collector::new();
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(stream, &mut collector) {
+                    eprintln!("{} Request failed: {}", "✗".red().bold(), e);
+                }
+            }
+            Err(e) => eprintln!("{} Accept failed: {}", "✗".red().bold(), e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle a single HTTP request.
+fn handle_connection(mut stream: TcpStream, collector: &mut Collector) -> Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Drain headers; we don't need any of them.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let path = path.split('?').next().unwrap_or(path);
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            collector.collect(),
+        ),
+        ("GET", "/") => (
+            "200 OK",
+            "text/plain; charset=utf-8",
+            "FGP metrics exporter. Metrics are at /metrics\n".to_string(),
+        ),
+        ("GET", _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found\n".to_string(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+
+    Ok(())
+}
+
+/// Probe every installed service.
+fn sample_services() -> Vec<ServiceSample> {
+    let services_dir = fgp_services_dir();
+    let entries = match fs::read_dir(&services_dir) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut samples: Vec<ServiceSample> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().to_str().map(sample_service))
+        .collect();
+
+    samples.sort_by(|a, b| a.name.cmp(&b.name));
+    samples
+}
+
+/// Probe a single service's health endpoint.
+fn sample_service(name: &str) -> ServiceSample {
+    let mut sample = ServiceSample {
+        name: name.to_string(),
+        up: false,
+        status: "stopped".to_string(),
+        version: None,
+        latency_secs: None,
+        uptime_secs: None,
+        pid: None,
+    };
+
+    let socket = service_socket_path(name);
+    if !socket.exists() {
+        return sample;
+    }
+
+    sample.status = "error".to_string();
+
+    let client = match fgp_daemon::FgpClient::new(&socket) {
+        Ok(c) => c,
+        Err(_) => return sample,
+    };
+
+    let start = Instant::now();
+    let response = match client.health() {
+        Ok(r) if r.ok => r,
+        _ => return sample,
+    };
+    sample.latency_secs = Some(start.elapsed().as_secs_f64());
+
+    let result = response.result.unwrap_or_default();
+    sample.up = true;
+    sample.status = result["status"].as_str().unwrap_or("healthy").to_string();
+    sample.version = result["version"].as_str().map(String::from);
+    sample.uptime_secs = result["uptime_seconds"].as_u64();
+    sample.pid = result["pid"]
+        .as_u64()
+        .and_then(|p| u32::try_from(p).ok())
+        .filter(|p| *p > 0);
+
+    sample
+}
+
+/// Render samples in Prometheus text exposition format.
+fn render(samples: &[ServiceSample], restarts: &HashMap<String, u64>, system: &System) -> String {
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        "fgp_up",
+        "gauge",
+        "Whether the daemon answered its health check.",
+    );
+    for s in samples {
+        let _ = writeln!(
+            out,
+            "fgp_up{{service=\"{}\"}} {}",
+            escape(&s.name),
+            s.up as u8
+        );
+    }
+
+    header(
+        &mut out,
+        "fgp_health_status",
+        "gauge",
+        "Daemon health status (1 for the current status, 0 otherwise).",
+    );
+    for s in samples {
+        let mut statuses: Vec<&str> = KNOWN_STATUSES.to_vec();
+        if !statuses.contains(&s.status.as_str()) {
+            statuses.push(&s.status);
+        }
+        for status in statuses {
+            let _ = writeln!(
+                out,
+                "fgp_health_status{{service=\"{}\",status=\"{}\"}} {}",
+                escape(&s.name),
+                escape(status),
+                (status == s.status) as u8
+            );
+        }
+    }
+
+    header(&mut out, "fgp_info", "gauge", "Daemon version information.");
+    for s in samples {
+        if let Some(version) = &s.version {
+            let _ = writeln!(
+                out,
+                "fgp_info{{service=\"{}\",version=\"{}\"}} 1",
+                escape(&s.name),
+                escape(version)
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "fgp_health_latency_seconds",
+        "gauge",
+        "Round-trip latency of the last health check.",
+    );
+    for s in samples {
+        if let Some(latency) = s.latency_secs {
+            let _ = writeln!(
+                out,
+                "fgp_health_latency_seconds{{service=\"{}\"}} {}",
+                escape(&s.name),
+                latency
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "fgp_uptime_seconds",
+        "gauge",
+        "Daemon uptime as reported by its health check.",
+    );
+    for s in samples {
+        if let Some(uptime) = s.uptime_secs {
+            let _ = writeln!(
+                out,
+                "fgp_uptime_seconds{{service=\"{}\"}} {}",
+                escape(&s.name),
+                uptime
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "fgp_restarts_total",
+        "counter",
+        "Restarts performed by the 'fgp monitor' watchdog.",
+    );
+    for s in samples {
+        let count = restarts.get(&s.name).copied().unwrap_or(0);
+        let _ = writeln!(
+            out,
+            "fgp_restarts_total{{service=\"{}\"}} {}",
+            escape(&s.name),
+            count
+        );
+    }
+
+    let processes: Vec<(&ServiceSample, &sysinfo::Process)> = samples
+        .iter()
+        .filter_map(|s| {
+            let pid = Pid::from_u32(s.pid?);
+            system.process(pid).map(|p| (s, p))
+        })
+        .collect();
+
+    header(
+        &mut out,
+        "fgp_process_cpu_usage_percent",
+        "gauge",
+        "Daemon process CPU usage (100 = one core).",
+    );
+    for (s, p) in &processes {
+        let _ = writeln!(
+            out,
+            "fgp_process_cpu_usage_percent{{service=\"{}\"}} {}",
+            escape(&s.name),
+            p.cpu_usage()
+        );
+    }
+
+    header(
+        &mut out,
+        "fgp_process_resident_memory_bytes",
+        "gauge",
+        "Daemon process resident memory.",
+    );
+    for (s, p) in &processes {
+        let _ = writeln!(
+            out,
+            "fgp_process_resident_memory_bytes{{service=\"{}\"}} {}",
+            escape(&s.name),
+            p.memory()
+        );
+    }
+
+    header(
+        &mut out,
+        "fgp_process_virtual_memory_bytes",
+        "gauge",
+        "Daemon process virtual memory.",
+    );
+    for (s, p) in &processes {
+        let _ = writeln!(
+            out,
+            "fgp_process_virtual_memory_bytes{{service=\"{}\"}} {}",
+            escape(&s.name),
+            p.virtual_memory()
+        );
+    }
+
+    out
+}
+
+/// Write HELP and TYPE lines for a metric family.
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a Prometheus label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn sample(name: &str, up: bool, status: &str) -> ServiceSample {
+        ServiceSample {
+            name: name.to_string(),
+            up,
+            status: status.to_string(),
+            version: None,
+            latency_secs: None,
+            uptime_secs: None,
+            pid: None,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut gmail = sample("gmail", true, "healthy");
+        gmail.version = Some("1.0 \"beta\"\\x".to_string());
+        gmail.latency_secs = Some(0.25);
+        gmail.uptime_secs = Some(60);
+        let samples = vec![
+            gmail,
+            sample("calendar", false, "stopped"),
+            sample("lazy", true, "idle"),
+        ];
+        let restarts = HashMap::from([("gmail".to_string(), 3)]);
+
+        let out = render(&samples, &restarts, &System::new());
+
+        // Every family has HELP and TYPE, even without series
+        for family in [
+            "fgp_up",
+            "fgp_health_status",
+            "fgp_info",
+            "fgp_health_latency_seconds",
+            "fgp_uptime_seconds",
+            "fgp_restarts_total",
+            "fgp_process_cpu_usage_percent",
+            "fgp_process_resident_memory_bytes",
+            "fgp_process_virtual_memory_bytes",
+        ] {
+            assert!(out.contains(&format!("# HELP {} ", family)), "{}", family);
+            assert!(out.contains(&format!("# TYPE {} ", family)), "{}", family);
+        }
+        assert!(out.contains("# TYPE fgp_restarts_total counter\n"));
+
+        assert!(out.contains("fgp_up{service=\"gmail\"} 1\n"));
+        assert!(out.contains("fgp_up{service=\"calendar\"} 0\n"));
+        assert!(out.contains("fgp_health_status{service=\"calendar\",status=\"stopped\"} 1\n"));
+        assert!(out.contains("fgp_health_status{service=\"calendar\",status=\"healthy\"} 0\n"));
+        // Statuses outside the known set get their own series
+        assert!(out.contains("fgp_health_status{service=\"lazy\",status=\"idle\"} 1\n"));
+        assert!(out.contains("fgp_info{service=\"gmail\",version=\"1.0 \\\"beta\\\"\\\\x\"} 1\n"));
+        assert!(out.contains("fgp_health_latency_seconds{service=\"gmail\"} 0.25\n"));
+        assert!(out.contains("fgp_uptime_seconds{service=\"gmail\"} 60\n"));
+        assert!(out.contains("fgp_restarts_total{service=\"gmail\"} 3\n"));
+        assert!(out.contains("fgp_restarts_total{service=\"calendar\"} 0\n"));
+        assert!(!out.contains("fgp_info{service=\"calendar\""));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("a\\b\"c\nd"), "a\\\\b\\\"c\\nd");
+    }
+
+    /// Send a GET for `path` and return the raw response.
+    fn get(collector: &mut Collector, path: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        write!(client, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        handle_connection(stream, collector).unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_handle_connection() {
+        let mut collector = Collector::new();
+
+        let response = get(&mut collector, "/metrics?x=1");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(response.contains("# TYPE fgp_up gauge\n"));
+
+        let response = get(&mut collector, "/other");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
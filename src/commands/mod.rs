@@ -1,17 +1,28 @@
 //! CLI command implementations.
 
+pub mod activator;
 pub mod agents;
 pub mod call;
 pub mod dashboard;
+pub mod doctor;
 pub mod generate;
 pub mod health;
+pub mod health_history;
 pub mod install;
 pub mod license;
+pub mod log_parser;
 pub mod logs;
 pub mod mcp_bridge;
+pub mod mcp_http;
+pub mod mcp_prompts;
+pub mod mcp_resources;
+pub mod mcp_workflows;
+pub mod method_diff;
 pub mod methods;
+pub mod metrics;
 pub mod monitor;
 pub mod new;
+pub mod service;
 pub mod skill;
 pub mod skill_export;
 pub mod skill_import;
@@ -20,9 +31,12 @@ pub mod skill_validate;
 pub mod start;
 pub mod status;
 pub mod stop;
+pub mod support_bundle;
 pub mod tui;
+pub mod verify;
 pub mod workflow;
 
+use anyhow::{bail, Result};
 use std::path::PathBuf;
 
 /// Get the FGP services directory.
@@ -36,8 +50,74 @@ pub fn service_socket_path(service: &str) -> PathBuf {
     fgp_services_dir().join(service).join("daemon.sock")
 }
 
+/// Names of all installed services (directories with a manifest), sorted.
+pub fn installed_services() -> Result<Vec<String>> {
+    let services_dir = fgp_services_dir();
+    if !services_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut names: Vec<String> = std::fs::read_dir(&services_dir)?
+        .flatten()
+        .filter(|e| e.path().join("manifest.json").exists())
+        .filter_map(|e| e.file_name().to_str().map(String::from))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
 /// Get the PID file path for a service.
 #[allow(dead_code)]
 pub fn service_pid_path(service: &str) -> PathBuf {
     fgp_services_dir().join(service).join("daemon.pid")
 }
+
+/// Parse a human-friendly duration such as "30s", "15m", "24h", "7d" or "2w".
+pub fn parse_duration(input: &str) -> Result<chrono::Duration> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (digits, unit) = input.split_at(split);
+
+    let value: i64 = match digits.parse() {
+        Ok(v) => v,
+        Err(_) => bail!("Invalid duration '{}'. Use e.g. 30s, 15m, 24h, 7d", input),
+    };
+
+    let unit_seconds: i64 = match unit.trim() {
+        "s" | "sec" | "secs" => 1,
+        "m" | "min" | "mins" => 60,
+        "h" | "hr" | "hrs" => 3_600,
+        "d" | "day" | "days" => 86_400,
+        "w" | "week" | "weeks" => 604_800,
+        other => bail!("Unknown duration unit '{}'. Use s, m, h, d or w", other),
+    };
+
+    // Callers subtract the duration from now, so it has to fit the calendar too
+    match value
+        .checked_mul(unit_seconds)
+        .and_then(chrono::Duration::try_seconds)
+        .filter(|d| chrono::Utc::now().checked_sub_signed(*d).is_some())
+    {
+        Some(duration) => Ok(duration),
+        None => bail!("Duration '{}' is too large", input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(
+            parse_duration("90s").unwrap(),
+            chrono::Duration::seconds(90)
+        );
+        assert_eq!(parse_duration("2w").unwrap(), chrono::Duration::days(14));
+        assert!(parse_duration("5y").is_err());
+        assert!(parse_duration("99999999999999d").is_err());
+        assert!(parse_duration("9223372036854775807s").is_err());
+    }
+}
@@ -3,22 +3,47 @@
 //! Watches FGP daemons and sends system notifications when services
 //! change state (crash, recover, go unhealthy). Optionally auto-restarts
 //! crashed services.
+//!
+//! Every probe is persisted to the health history store (see
+//! `health_history`) for `fgp health <service> --history`.
+//!
+//! Every state transition can also be emitted as a structured JSON event:
+//! appended to a JSONL file, piped to a user hook command, or POSTed to a
+//! webhook.
 
 use anyhow::Result;
 use colored::Colorize;
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::notifications;
+use crate::notifications::{self, Urgency};
+
+use super::health_history::{self, HealthProbe};
 
 // Use shared helpers from parent module
-use super::{fgp_services_dir, service_socket_path};
+use super::{fgp_services_dir, service, service_socket_path};
+
+/// Sound played for critical alerts (crashes, unexpected stops).
+#[cfg(target_os = "macos")]
+const CRASH_SOUND: &str = "Basso";
+#[cfg(not(target_os = "macos"))]
+const CRASH_SOUND: &str = "dialog-warning";
+
+/// How often old health history is pruned.
+const HISTORY_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Timeout for webhook delivery so a slow endpoint can't stall the monitor loop.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Service state for tracking changes.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 enum ServiceState {
     Running,
     Stopped,
@@ -40,6 +65,33 @@ struct RestartTracker {
     attempts: HashMap<String, u32>,
 }
 
+/// Destinations for structured state-change events.
+#[derive(Debug, Clone, Default)]
+pub struct EventHooks {
+    /// Append each event as a JSON line to this file.
+    pub events_file: Option<PathBuf>,
+    /// Command run (via `sh -c`) for each event, with the event JSON on stdin.
+    pub on_event: Option<String>,
+    /// URL each event is POSTed to as JSON.
+    pub webhook: Option<String>,
+}
+
+impl EventHooks {
+    fn is_empty(&self) -> bool {
+        self.events_file.is_none() && self.on_event.is_none() && self.webhook.is_none()
+    }
+}
+
+/// A structured state-change event.
+#[derive(Debug, Serialize)]
+struct MonitorEvent<'a> {
+    timestamp: String,
+    service: &'a str,
+    event: &'static str,
+    previous: &'a ServiceState,
+    current: &'a ServiceState,
+}
+
 /// Run the health monitor.
 pub fn run(
     interval_secs: u64,
@@ -47,6 +99,7 @@ pub fn run(
     auto_restart: bool,
     max_restarts: u32,
     restart_delay_secs: u64,
+    hooks: EventHooks,
 ) -> Result<()> {
     let watchdog = WatchdogConfig {
         enabled: auto_restart,
@@ -81,14 +134,34 @@ pub fn run(
             restart_delay_secs
         );
     }
+
+    if let Some(path) = &hooks.events_file {
+        println!("{} Writing events to {}", "→".blue().bold(), path.display());
+    }
+    if let Some(cmd) = &hooks.on_event {
+        println!("{} Event hook: {}", "→".blue().bold(), cmd);
+    }
+    if let Some(url) = &hooks.webhook {
+        println!("{} Event webhook: {}", "→".blue().bold(), url);
+    }
     println!();
 
     let mut states: HashMap<String, ServiceState> = HashMap::new();
     let mut restart_tracker = RestartTracker::default();
     let interval = Duration::from_secs(interval_secs);
+    let mut last_prune: Option<Instant> = None;
 
     loop {
-        check_services(&mut states, &watchdog, &mut restart_tracker);
+        let prune_due = match last_prune {
+            Some(t) => t.elapsed() >= HISTORY_PRUNE_INTERVAL,
+            None => true,
+        };
+        if prune_due {
+            let _ = health_history::prune_all();
+            last_prune = Some(Instant::now());
+        }
+
+        check_services(&mut states, &watchdog, &mut restart_tracker, &hooks);
         thread::sleep(interval);
     }
 }
@@ -98,6 +171,7 @@ fn check_services(
     states: &mut HashMap<String, ServiceState>,
     watchdog: &WatchdogConfig,
     restart_tracker: &mut RestartTracker,
+    hooks: &EventHooks,
 ) {
     let services_dir = fgp_services_dir();
 
@@ -117,12 +191,29 @@ fn check_services(
         };
 
         let socket = service_socket_path(&name);
-        let current_state = get_service_state(&socket);
+        let (current_state, probe) = probe_service(&socket);
+
+        if let Err(e) = health_history::record(&name, &probe) {
+            eprintln!(
+                "[{}] {} Failed to record health history for {}: {}",
+                chrono::Local::now().format("%H:%M:%S"),
+                "⚠".yellow().bold(),
+                name,
+                e
+            );
+        }
 
         // Check for state transitions
         if let Some(prev_state) = states.get(&name) {
             if *prev_state != current_state {
-                handle_state_change(&name, prev_state, &current_state, watchdog, restart_tracker);
+                handle_state_change(
+                    &name,
+                    prev_state,
+                    &current_state,
+                    watchdog,
+                    restart_tracker,
+                    hooks,
+                );
 
                 // Reset restart counter if service came back up
                 if current_state == ServiceState::Running {
@@ -135,28 +226,52 @@ fn check_services(
     }
 }
 
-/// Get the current state of a service.
-fn get_service_state(socket: &PathBuf) -> ServiceState {
+/// Probe a service's health, returning its state and a history record.
+fn probe_service(socket: &Path) -> (ServiceState, HealthProbe) {
+    let mut probe = HealthProbe {
+        timestamp: chrono::Utc::now(),
+        status: "stopped".to_string(),
+        latency_ms: None,
+        services: BTreeMap::new(),
+    };
+
     if !socket.exists() {
-        return ServiceState::Stopped;
+        return (ServiceState::Stopped, probe);
     }
 
-    match fgp_daemon::FgpClient::new(socket) {
-        Ok(client) => match client.health() {
-            Ok(response) if response.ok => {
-                let result = response.result.unwrap_or_default();
-                let status = result["status"].as_str().unwrap_or("running");
+    probe.status = "error".to_string();
 
-                match status {
-                    "healthy" | "running" => ServiceState::Running,
-                    "degraded" | "unhealthy" => ServiceState::Unhealthy,
-                    _ => ServiceState::Running,
-                }
-            }
-            _ => ServiceState::Error,
-        },
-        Err(_) => ServiceState::Error,
+    let client = match fgp_daemon::FgpClient::new(socket) {
+        Ok(client) => client,
+        Err(_) => return (ServiceState::Error, probe),
+    };
+
+    let start = Instant::now();
+    let response = match client.health() {
+        Ok(response) if response.ok => response,
+        _ => return (ServiceState::Error, probe),
+    };
+    probe.latency_ms = Some(start.elapsed().as_secs_f64() * 1000.0);
+
+    let result = response.result.unwrap_or_default();
+    let status = result["status"].as_str().unwrap_or("running");
+    probe.status = status.to_string();
+
+    if let Some(services) = result["services"].as_object() {
+        for (sub, sub_status) in services {
+            let ok = sub_status["ok"].as_bool().unwrap_or(false);
+            probe.services.insert(sub.clone(), ok);
+        }
     }
+
+    let state = match status {
+        // "idle" comes from `fgp activator`, which starts the daemon on demand
+        "healthy" | "running" | "idle" => ServiceState::Running,
+        "degraded" | "unhealthy" => ServiceState::Unhealthy,
+        _ => ServiceState::Running,
+    };
+
+    (state, probe)
 }
 
 /// Handle a state change and send notifications.
@@ -166,24 +281,29 @@ fn handle_state_change(
     current: &ServiceState,
     watchdog: &WatchdogConfig,
     restart_tracker: &mut RestartTracker,
+    hooks: &EventHooks,
 ) {
+    emit_event(hooks, name, prev, current);
+
     let should_restart = matches!(
         (prev, current),
         (ServiceState::Running, ServiceState::Error)
             | (ServiceState::Running, ServiceState::Stopped)
     );
 
-    let (title, message, log_style) = match (prev, current) {
+    let (title, message, log_style, urgency) = match (prev, current) {
         // Service crashed (was running, now error or stopped)
         (ServiceState::Running, ServiceState::Error) => (
             "FGP Service Crashed",
             format!("{} daemon crashed", name),
             format!("{} {} crashed", "✗".red().bold(), name),
+            Urgency::Critical,
         ),
         (ServiceState::Running, ServiceState::Stopped) => (
             "FGP Service Stopped",
             format!("{} daemon stopped unexpectedly", name),
             format!("{} {} stopped", "○".dimmed(), name),
+            Urgency::Critical,
         ),
 
         // Service went unhealthy
@@ -191,6 +311,7 @@ fn handle_state_change(
             "FGP Service Unhealthy",
             format!("{} daemon is unhealthy", name),
             format!("{} {} is unhealthy", "◐".yellow().bold(), name),
+            Urgency::Normal,
         ),
 
         // Service recovered
@@ -198,16 +319,19 @@ fn handle_state_change(
             "FGP Service Recovered",
             format!("{} daemon recovered", name),
             format!("{} {} recovered", "✓".green().bold(), name),
+            Urgency::Low,
         ),
         (ServiceState::Error, ServiceState::Running) => (
             "FGP Service Started",
             format!("{} daemon is now running", name),
             format!("{} {} started", "●".green().bold(), name),
+            Urgency::Low,
         ),
         (ServiceState::Stopped, ServiceState::Running) => (
             "FGP Service Started",
             format!("{} daemon started", name),
             format!("{} {} started", "●".green().bold(), name),
+            Urgency::Low,
         ),
 
         // Other transitions - just log, no notification
@@ -230,8 +354,12 @@ fn handle_state_change(
         log_style
     );
 
-    // Send system notification
-    notifications::notify(title, &message);
+    // Send system notification; crashes get an audible alert
+    if urgency == Urgency::Critical {
+        notifications::notify_with_sound(title, &message, urgency, CRASH_SOUND);
+    } else {
+        notifications::notify(title, &message, urgency);
+    }
 
     // Auto-restart if enabled and service crashed
     if watchdog.enabled && should_restart {
@@ -239,6 +367,165 @@ fn handle_state_change(
     }
 }
 
+/// Get the path of the persisted watchdog restart counters.
+fn restart_counts_path() -> PathBuf {
+    let base = shellexpand::tilde("~/.fgp/health/restarts.json");
+    PathBuf::from(base.as_ref())
+}
+
+/// Total restarts performed by the watchdog, per service.
+///
+/// Unlike `RestartTracker`, these never reset, so they can be exported as counters.
+pub fn restart_counts() -> HashMap<String, u64> {
+    fs::read_to_string(restart_counts_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Increment the persisted restart counter for a service.
+fn record_restart(name: &str) -> Result<()> {
+    let mut counts = restart_counts();
+    *counts.entry(name.to_string()).or_insert(0) += 1;
+
+    let path = restart_counts_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&counts)?)?;
+    Ok(())
+}
+
+/// Classify a state transition for event consumers.
+fn event_kind(prev: &ServiceState, current: &ServiceState) -> &'static str {
+    match (prev, current) {
+        (ServiceState::Running, ServiceState::Error) => "crashed",
+        (ServiceState::Running, ServiceState::Stopped) => "stopped",
+        (ServiceState::Running, ServiceState::Unhealthy) => "unhealthy",
+        (ServiceState::Unhealthy, ServiceState::Running) => "recovered",
+        (_, ServiceState::Running) => "started",
+        _ => "changed",
+    }
+}
+
+/// Emit a state-change event to every configured hook.
+///
+/// Hook failures are reported on stderr but never stop the monitor.
+fn emit_event(hooks: &EventHooks, name: &str, prev: &ServiceState, current: &ServiceState) {
+    if hooks.is_empty() {
+        return;
+    }
+
+    let event = MonitorEvent {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        service: name,
+        event: event_kind(prev, current),
+        previous: prev,
+        current,
+    };
+
+    let json = match serde_json::to_string(&event) {
+        Ok(j) => j,
+        Err(_) => return,
+    };
+
+    if let Some(path) = &hooks.events_file {
+        if let Err(e) = append_event_line(path, &json) {
+            warn_hook_failure("events file", &e.to_string());
+        }
+    }
+
+    if let Some(cmd) = &hooks.on_event {
+        if let Err(e) = run_event_hook(cmd, &event, &json) {
+            warn_hook_failure("event hook", &e.to_string());
+        }
+    }
+
+    if let Some(url) = &hooks.webhook {
+        if let Err(e) = post_event(url, &json) {
+            warn_hook_failure("webhook", &e.to_string());
+        }
+    }
+}
+
+/// Append one JSON event line to the events file.
+fn append_event_line(path: &Path, json: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", json)
+}
+
+/// Run the user's hook command with event data in `FGP_*` env vars and JSON on stdin.
+///
+/// The hook runs detached; a reaper thread waits on it so slow hooks don't block polling.
+fn run_event_hook(cmd: &str, event: &MonitorEvent, json: &str) -> std::io::Result<()> {
+    let mut child = Command::new("sh")
+        .args(["-c", cmd])
+        .env("FGP_EVENT", event.event)
+        .env("FGP_SERVICE", event.service)
+        .env("FGP_PREVIOUS_STATE", state_name(event.previous))
+        .env("FGP_STATE", state_name(event.current))
+        .env("FGP_TIMESTAMP", &event.timestamp)
+        .env("FGP_EVENT_JSON", json)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores stdin may close it early; that's not an error.
+        let _ = writeln!(stdin, "{}", json);
+    }
+
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    Ok(())
+}
+
+/// POST an event to the webhook URL.
+fn post_event(url: &str, json: &str) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()?;
+
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(json.to_string())
+        .send()?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("HTTP {}", response.status());
+    }
+
+    Ok(())
+}
+
+/// Lowercase state name as used in events.
+fn state_name(state: &ServiceState) -> &'static str {
+    match state {
+        ServiceState::Running => "running",
+        ServiceState::Stopped => "stopped",
+        ServiceState::Unhealthy => "unhealthy",
+        ServiceState::Error => "error",
+    }
+}
+
+/// Report a hook delivery failure without interrupting monitoring.
+fn warn_hook_failure(hook: &str, error: &str) {
+    eprintln!(
+        "[{}] {} Failed to deliver event to {}: {}",
+        chrono::Local::now().format("%H:%M:%S"),
+        "⚠".yellow().bold(),
+        hook,
+        error
+    );
+}
+
 /// Attempt to restart a crashed service.
 fn attempt_restart(name: &str, watchdog: &WatchdogConfig, restart_tracker: &mut RestartTracker) {
     let attempts = restart_tracker.attempts.entry(name.to_string()).or_insert(0);
@@ -256,6 +543,7 @@ fn attempt_restart(name: &str, watchdog: &WatchdogConfig, restart_tracker: &mut
         notifications::notify(
             "FGP Restart Limit Reached",
             &format!("{} exceeded {} restart attempts", name, watchdog.max_restarts),
+            Urgency::Critical,
         );
         return;
     }
@@ -276,9 +564,19 @@ fn attempt_restart(name: &str, watchdog: &WatchdogConfig, restart_tracker: &mut
     // Wait before restarting
     thread::sleep(watchdog.restart_delay);
 
-    // Attempt to start the service
-    match fgp_daemon::lifecycle::start_service(name) {
+    // Attempt to start the service (through systemd if it manages the daemon)
+    let restart_result = if service::has_unit(name) {
+        service::systemctl(&["restart", &service::unit_name(name)])
+    } else {
+        fgp_daemon::lifecycle::start_service(name).map_err(|e| anyhow::anyhow!("{}", e))
+    };
+
+    match restart_result {
         Ok(()) => {
+            // Only restarts that actually happened count towards the exported total
+            if let Err(e) = record_restart(name) {
+                warn_hook_failure("restart counter", &e.to_string());
+            }
             println!(
                 "[{}] {} {} restart initiated",
                 chrono::Local::now().format("%H:%M:%S"),
@@ -297,7 +595,107 @@ fn attempt_restart(name: &str, watchdog: &WatchdogConfig, restart_tracker: &mut
             notifications::notify(
                 "FGP Restart Failed",
                 &format!("Failed to restart {}: {}", name, e),
+                Urgency::Critical,
             );
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_events_file_gets_one_json_line_per_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("events.jsonl");
+        let hooks = EventHooks {
+            events_file: Some(path.clone()),
+            ..Default::default()
+        };
+
+        emit_event(
+            &hooks,
+            "gmail",
+            &ServiceState::Running,
+            &ServiceState::Error,
+        );
+        emit_event(
+            &hooks,
+            "gmail",
+            &ServiceState::Error,
+            &ServiceState::Running,
+        );
+
+        let content = fs::read_to_string(&path).unwrap();
+        let events: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["service"], "gmail");
+        assert_eq!(events[0]["event"], "crashed");
+        assert_eq!(events[0]["previous"], "running");
+        assert_eq!(events[0]["current"], "error");
+        assert!(
+            chrono::DateTime::parse_from_rfc3339(events[0]["timestamp"].as_str().unwrap()).is_ok()
+        );
+        assert_eq!(events[1]["event"], "started");
+    }
+
+    #[test]
+    fn test_event_hook_gets_env_and_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("hook.out");
+        let hooks = EventHooks {
+            on_event: Some(format!(
+                "{{ echo \"$FGP_EVENT $FGP_SERVICE $FGP_PREVIOUS_STATE $FGP_STATE\"; cat; }} > {}.tmp && mv {}.tmp {}",
+                out.display(),
+                out.display(),
+                out.display()
+            )),
+            ..Default::default()
+        };
+
+        emit_event(
+            &hooks,
+            "slack",
+            &ServiceState::Running,
+            &ServiceState::Unhealthy,
+        );
+
+        // The hook runs detached; wait for it to finish writing
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !out.exists() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(20));
+        }
+        let content = fs::read_to_string(&out).expect("hook should have run");
+        let mut lines = content.lines();
+        assert_eq!(lines.next(), Some("unhealthy slack running unhealthy"));
+        let payload: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(payload["service"], "slack");
+        assert_eq!(payload["event"], "unhealthy");
+    }
+
+    #[test]
+    fn test_webhook_times_out() {
+        // Accepts the connection but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                line.clear();
+            }
+        });
+
+        let started = Instant::now();
+        assert!(post_event(&url, "{}").is_err());
+        assert!(started.elapsed() < WEBHOOK_TIMEOUT + Duration::from_secs(3));
+        drop(server);
+    }
+}
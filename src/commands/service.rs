@@ -0,0 +1,387 @@
+//! Generate systemd user units for FGP daemons and the health monitor.
+//!
+//! Units are written to `~/.config/systemd/user/fgp-<service>.service`.
+//! When a unit is installed, `fgp start/stop/status` delegate to `systemctl --user`
+//! instead of spawning detached children.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{fgp_services_dir, service_socket_path};
+
+/// Unit name used for the health monitor.
+const MONITOR_UNIT: &str = "fgp-monitor.service";
+
+/// Default systemd `Restart=` policy when the manifest doesn't declare one.
+const DEFAULT_RESTART_POLICY: &str = "on-failure";
+
+/// Restart policies systemd accepts.
+const RESTART_POLICIES: &[&str] = &[
+    "no",
+    "on-success",
+    "on-failure",
+    "on-abnormal",
+    "on-watchdog",
+    "on-abort",
+    "always",
+];
+
+/// Settings for a daemon's `[Service]` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitSpec {
+    pub description: String,
+    pub exec_start: PathBuf,
+    pub working_dir: PathBuf,
+    pub env_file: Option<PathBuf>,
+    pub restart: String,
+    pub socket: PathBuf,
+}
+
+/// Get the systemd user unit directory.
+fn unit_dir() -> PathBuf {
+    let base = shellexpand::tilde("~/.config/systemd/user");
+    PathBuf::from(base.as_ref())
+}
+
+/// Get the unit name for a service.
+pub fn unit_name(service: &str) -> String {
+    format!("fgp-{}.service", service)
+}
+
+/// Whether a systemd user unit is installed for a service.
+pub fn has_unit(service: &str) -> bool {
+    cfg!(target_os = "linux") && unit_dir().join(unit_name(service)).exists()
+}
+
+/// Run `systemctl --user <args>`, failing on non-zero exit.
+pub fn systemctl(args: &[&str]) -> Result<()> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .context("Failed to run systemctl. Is systemd available?")?;
+
+    if !output.status.success() {
+        bail!(
+            "systemctl --user {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Get the `systemctl --user is-active` state of a service's unit (e.g. "active", "failed").
+pub fn unit_state(service: &str) -> String {
+    Command::new("systemctl")
+        .args(["--user", "is-active", &unit_name(service)])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Build a unit spec from a service's installed manifest.
+///
+/// Reads `daemon.entrypoint` plus the optional `daemon.working_dir`,
+/// `daemon.env_file` and `daemon.restart` keys. Without an explicit env file,
+/// `<service dir>/.env` is used if present.
+pub fn spec_from_manifest(service: &str) -> Result<UnitSpec> {
+    let service_dir = fgp_services_dir().join(service);
+    let manifest_path = service_dir.join("manifest.json");
+
+    if !manifest_path.exists() {
+        bail!(
+            "Service '{}' is not installed. Run 'fgp install <path>' first.",
+            service
+        );
+    }
+
+    let manifest_content =
+        fs::read_to_string(&manifest_path).context("Failed to read manifest.json")?;
+    let manifest: serde_json::Value =
+        serde_json::from_str(&manifest_content).context("Failed to parse manifest.json")?;
+
+    let daemon = &manifest["daemon"];
+    let entrypoint = daemon["entrypoint"]
+        .as_str()
+        .context("manifest.json missing daemon.entrypoint")?;
+
+    let working_dir = daemon["working_dir"]
+        .as_str()
+        .map(|d| resolve(&service_dir, d))
+        .unwrap_or_else(|| service_dir.clone());
+
+    let env_file = match daemon["env_file"].as_str() {
+        Some(f) => Some(resolve(&service_dir, f)),
+        None => Some(service_dir.join(".env")).filter(|p| p.exists()),
+    };
+
+    let restart = daemon["restart"]
+        .as_str()
+        .unwrap_or(DEFAULT_RESTART_POLICY)
+        .to_string();
+    if !RESTART_POLICIES.contains(&restart.as_str()) {
+        bail!(
+            "Invalid daemon.restart '{}' in manifest.json. Expected one of: {}",
+            restart,
+            RESTART_POLICIES.join(", ")
+        );
+    }
+
+    let description = manifest["description"]
+        .as_str()
+        .filter(|d| !d.is_empty())
+        .map(|d| format!("FGP {} daemon - {}", service, d))
+        .unwrap_or_else(|| format!("FGP {} daemon", service));
+
+    Ok(UnitSpec {
+        description,
+        exec_start: resolve(&service_dir, entrypoint),
+        working_dir,
+        env_file,
+        restart,
+        socket: service_socket_path(service),
+    })
+}
+
+/// Resolve a manifest path relative to the service directory.
+pub fn resolve(service_dir: &Path, path: &str) -> PathBuf {
+    let expanded = shellexpand::tilde(path);
+    let path = Path::new(expanded.as_ref());
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        service_dir.join(path.strip_prefix("./").unwrap_or(path))
+    }
+}
+
+/// A path for a systemd setting, with `%` escaped so it isn't read as a specifier.
+///
+/// Settings such as `WorkingDirectory=` take the rest of the line verbatim, so
+/// no quoting is needed (or allowed).
+fn unit_path(path: &Path) -> String {
+    path.to_string_lossy().replace('%', "%%")
+}
+
+/// Free text for a single-line setting such as `Description=`.
+///
+/// Line breaks become spaces so manifest text can't add directives, `%` is
+/// escaped, and a trailing backslash (a line continuation) is dropped.
+fn unit_text(text: &str) -> String {
+    text.replace(['\n', '\r'], " ")
+        .replace('%', "%%")
+        .trim_end_matches('\\')
+        .to_string()
+}
+
+/// Quote a path for use in a systemd command line (`ExecStart=` and friends).
+fn quote(path: &Path) -> String {
+    // Command lines also expand `$VAR`
+    let s = unit_path(path).replace('$', "$$");
+    if s.contains(char::is_whitespace) || s.contains(['"', '\'', '\\']) {
+        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        s
+    }
+}
+
+/// Render a daemon unit file.
+///
+/// Units and `fgp activator` are mutually exclusive: the unit's `ExecStopPost=`
+/// removes `daemon.sock`, which the activator owns while it runs.
+pub fn render_service_unit(spec: &UnitSpec) -> String {
+    let mut unit = String::new();
+
+    unit.push_str("[Unit]\n");
+    unit.push_str(&format!("Description={}\n", unit_text(&spec.description)));
+    unit.push_str("Documentation=https://github.com/fast-gateway-protocol\n");
+    unit.push('\n');
+
+    unit.push_str("[Service]\n");
+    unit.push_str("Type=simple\n");
+    unit.push_str(&format!(
+        "WorkingDirectory={}\n",
+        unit_path(&spec.working_dir)
+    ));
+    if let Some(env_file) = &spec.env_file {
+        // Leading '-' tolerates the file being removed later
+        unit.push_str(&format!("EnvironmentFile=-{}\n", unit_path(env_file)));
+    }
+    unit.push_str(&format!("ExecStart={}\n", quote(&spec.exec_start)));
+    // Remove a stale socket so clients don't connect to a dead daemon
+    unit.push_str(&format!(
+        "ExecStopPost=/bin/rm -f {}\n",
+        quote(&spec.socket)
+    ));
+    unit.push_str(&format!("Restart={}\n", spec.restart));
+    unit.push_str("RestartSec=5\n");
+    unit.push('\n');
+
+    unit.push_str("[Install]\n");
+    unit.push_str("WantedBy=default.target\n");
+
+    unit
+}
+
+/// Render the unit file for `fgp monitor`.
+pub fn render_monitor_unit(fgp_bin: &Path, interval: u64, auto_restart: bool) -> String {
+    let mut exec = format!("{} monitor --interval {}", quote(fgp_bin), interval);
+    if auto_restart {
+        exec.push_str(" --auto-restart");
+    }
+
+    format!(
+        "[Unit]\n\
+         Description=FGP health monitor\n\
+         Documentation=https://github.com/fast-gateway-protocol\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         ExecStart={}\n\
+         Restart=on-failure\n\
+         RestartSec=10\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        exec
+    )
+}
+
+/// Generate (and optionally install) a daemon's unit (`fgp service unit <service>`).
+pub fn unit(service: &str, install: bool) -> Result<()> {
+    let spec = spec_from_manifest(service)?;
+    let content = render_service_unit(&spec);
+
+    if install {
+        install_unit(&unit_name(service), &content)?;
+        println!();
+        println!("Start with: {}", format!("fgp start {}", service).cyan());
+    } else {
+        print!("{}", content);
+    }
+
+    Ok(())
+}
+
+/// Generate (and optionally install) the monitor unit (`fgp service monitor-unit`).
+pub fn monitor_unit(install: bool, interval: u64, auto_restart: bool) -> Result<()> {
+    let fgp_bin = std::env::current_exe().context("Failed to locate the fgp binary")?;
+    let content = render_monitor_unit(&fgp_bin, interval, auto_restart);
+
+    if install {
+        install_unit(MONITOR_UNIT, &content)?;
+        systemctl(&["start", MONITOR_UNIT])?;
+        println!("{} {} started", "✓".green().bold(), MONITOR_UNIT.bold());
+    } else {
+        print!("{}", content);
+    }
+
+    Ok(())
+}
+
+/// Stop, disable and delete a daemon's unit (`fgp service remove <service>`).
+pub fn remove(service: &str) -> Result<()> {
+    let name = unit_name(service);
+    let path = unit_dir().join(&name);
+
+    if !path.exists() {
+        println!(
+            "{} No systemd unit installed for '{}'.",
+            "!".yellow().bold(),
+            service
+        );
+        return Ok(());
+    }
+
+    // The unit may already be stopped/disabled; only the file removal must succeed.
+    let _ = systemctl(&["disable", "--now", &name]);
+    fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    systemctl(&["daemon-reload"])?;
+
+    println!("{} Removed {}", "✓".green().bold(), name.bold());
+    Ok(())
+}
+
+/// Write a unit file, reload systemd and enable the unit.
+fn install_unit(name: &str, content: &str) -> Result<()> {
+    if !cfg!(target_os = "linux") {
+        bail!("systemd units are only supported on Linux");
+    }
+
+    let dir = unit_dir();
+    fs::create_dir_all(&dir).context("Failed to create systemd user unit directory")?;
+
+    let path = dir.join(name);
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("{} Wrote {}", "✓".green().bold(), path.display());
+
+    systemctl(&["daemon-reload"])?;
+    systemctl(&["enable", name])?;
+    println!("{} Enabled {}", "✓".green().bold(), name.bold());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_service_unit() {
+        let spec = UnitSpec {
+            description: "FGP gmail daemon".to_string(),
+            exec_start: PathBuf::from("/home/u/.fgp/services/gmail/fgp-gmail"),
+            working_dir: PathBuf::from("/home/u/.fgp/services/gmail"),
+            env_file: Some(PathBuf::from("/home/u/My Env/.env")),
+            restart: "always".to_string(),
+            socket: PathBuf::from("/home/u/.fgp/services/gmail/daemon.sock"),
+        };
+
+        let unit = render_service_unit(&spec);
+        assert!(unit.contains("ExecStart=/home/u/.fgp/services/gmail/fgp-gmail\n"));
+        assert!(unit.contains("WorkingDirectory=/home/u/.fgp/services/gmail\n"));
+        // Only command lines are quoted; systemd reads other paths verbatim
+        assert!(unit.contains("EnvironmentFile=-/home/u/My Env/.env\n"));
+        assert!(unit.contains("Restart=always\n"));
+        assert!(unit.contains("WantedBy=default.target\n"));
+    }
+
+    #[test]
+    fn test_render_service_unit_escapes_paths() {
+        let spec = UnitSpec {
+            description: "FGP odd daemon".to_string(),
+            exec_start: PathBuf::from("/opt/My Apps/100%/fgp-$odd"),
+            working_dir: PathBuf::from("/opt/My Apps/100%"),
+            env_file: None,
+            restart: "on-failure".to_string(),
+            socket: PathBuf::from("/home/u/.fgp/services/odd/daemon.sock"),
+        };
+
+        let unit = render_service_unit(&spec);
+        assert!(unit.contains("ExecStart=\"/opt/My Apps/100%%/fgp-$$odd\"\n"));
+        assert!(unit.contains("WorkingDirectory=/opt/My Apps/100%%\n"));
+        assert!(!unit.contains("EnvironmentFile="));
+    }
+
+    #[test]
+    fn test_render_service_unit_escapes_description() {
+        let spec = UnitSpec {
+            description: "100% evil\nExecStartPre=/bin/rm -rf ~\r\n[Install]\\".to_string(),
+            exec_start: PathBuf::from("/opt/evil/fgp-evil"),
+            working_dir: PathBuf::from("/opt/evil"),
+            env_file: None,
+            restart: "on-failure".to_string(),
+            socket: PathBuf::from("/home/u/.fgp/services/evil/daemon.sock"),
+        };
+
+        let unit = render_service_unit(&spec);
+        assert!(unit.contains(
+            "Description=100%% evil ExecStartPre=/bin/rm -rf ~  [Install]\nDocumentation="
+        ));
+        assert!(!unit.lines().any(|l| l.starts_with("ExecStartPre=")));
+    }
+}
@@ -348,12 +348,12 @@ fn fgp_home() -> PathBuf {
 }
 
 /// Get the skills directory
-fn skills_dir() -> PathBuf {
+pub fn skills_dir() -> PathBuf {
     fgp_home().join("skills")
 }
 
 /// Get the installed skills file path
-fn installed_skills_path() -> PathBuf {
+pub fn installed_skills_path() -> PathBuf {
     skills_dir().join("installed_skills.json")
 }
 
@@ -385,6 +385,27 @@ fn load_installed_skills() -> Result<InstalledSkills> {
     Ok(serde_json::from_str(&content)?)
 }
 
+/// Source directories and parsed `skill.yaml` of installed tap skills.
+///
+/// Skills without a readable `skill.yaml` (e.g. marketplace installs) are skipped.
+pub fn installed_skill_manifests() -> Vec<(PathBuf, super::skill_validate::SkillManifest)> {
+    let Ok(installed) = load_installed_skills() else {
+        return Vec::new();
+    };
+
+    installed
+        .skills
+        .values()
+        .filter_map(|entries| entries.first())
+        .filter_map(|entry| {
+            let source = Path::new(&entry.install_path).join("source");
+            let content = fs::read_to_string(source.join("skill.yaml")).ok()?;
+            let manifest = serde_yaml::from_str(&content).ok()?;
+            Some((source, manifest))
+        })
+        .collect()
+}
+
 /// Save installed skills
 fn save_installed_skills(skills: &InstalledSkills) -> Result<()> {
     let path = installed_skills_path();
@@ -2275,7 +2296,7 @@ fn export_to_cursor(skill: &SkillManifest) -> Result<()> {
     // Add FGP server entry
     let server_entry = serde_json::json!({
         "command": "fgp",
-        "args": ["mcp", "--service", &daemon_name],
+        "args": ["mcp", "serve", "--service", &daemon_name],
         "env": {}
     });
 
@@ -13,6 +13,7 @@ use colored::Colorize;
 use std::fs;
 use std::path::Path;
 
+use super::methods;
 use super::skill_validate::SkillManifest;
 
 /// Export a skill for a specific agent.
@@ -287,15 +288,37 @@ fn export_mcp(manifest: &SkillManifest, _skill_dir: &Path, output_dir: &Path) ->
     let mut mcp_tools = Vec::new();
 
     for daemon in &manifest.daemons {
+        if daemon.methods.is_empty() {
+            continue;
+        }
+
+        // Param definitions come from the daemon, or its installed manifest
+        let specs = match methods::load(&daemon.name) {
+            Ok((specs, _)) => specs,
+            Err(_) => {
+                println!(
+                    "  {} Couldn't load {} methods; its tools will have no parameters",
+                    "!".yellow().bold(),
+                    daemon.name
+                );
+                Vec::new()
+            }
+        };
+
         for method in &daemon.methods {
+            let qualified = format!("{}.{}", daemon.name, method);
+            let spec = specs
+                .iter()
+                .find(|s| s.name == *method || s.name == qualified);
+            let description = spec
+                .and_then(|s| s.description.clone())
+                .unwrap_or_else(|| format!("{} via FGP {} daemon", method, daemon.name));
+            let params = spec.map(|s| s.params.as_slice()).unwrap_or_default();
+
             mcp_tools.push(serde_json::json!({
                 "name": format!("{}_{}", prefix, method),
-                "description": format!("{} via FGP {} daemon", method, daemon.name),
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {},
-                    "required": []
-                }
+                "description": description,
+                "inputSchema": methods::params_schema(params)
             }));
         }
     }
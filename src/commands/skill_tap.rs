@@ -87,7 +87,7 @@ fn taps_dir() -> PathBuf {
 }
 
 /// Get the taps config file path
-fn taps_config_path() -> PathBuf {
+pub fn taps_config_path() -> PathBuf {
     taps_dir().join("taps.json")
 }
 
@@ -97,7 +97,7 @@ fn repos_dir() -> PathBuf {
 }
 
 /// Load taps configuration
-fn load_taps_config() -> Result<TapsConfig> {
+pub fn load_taps_config() -> Result<TapsConfig> {
     let path = taps_config_path();
     if !path.exists() {
         return Ok(TapsConfig::default());
@@ -107,7 +107,7 @@ fn load_taps_config() -> Result<TapsConfig> {
 }
 
 /// Save taps configuration
-fn save_taps_config(config: &TapsConfig) -> Result<()> {
+pub fn save_taps_config(config: &TapsConfig) -> Result<()> {
     let path = taps_config_path();
     fs::create_dir_all(path.parent().unwrap())?;
     let content = serde_json::to_string_pretty(config)?;
@@ -8,7 +8,7 @@ use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Skill manifest (skill.yaml) - the composed skill format.
 #[derive(Debug, Serialize, Deserialize)]
@@ -457,26 +457,44 @@ fn validate_daemons(daemons: &[DaemonDependency]) -> Result<()> {
     Ok(())
 }
 
+/// A file inside a skill directory, resolved through symlinks.
+///
+/// `None` if it doesn't exist or resolves outside `skill_dir` (an absolute
+/// path, `..`, or a symlink), so manifests can't point at arbitrary files.
+pub fn skill_file(skill_dir: &Path, file: &str) -> Option<PathBuf> {
+    let dir = skill_dir.canonicalize().ok()?;
+    let path = dir.join(file).canonicalize().ok()?;
+    path.starts_with(&dir).then_some(path)
+}
+
 fn validate_instructions(
     instructions: &Instructions,
     skill_dir: &Path,
     warnings: &mut Vec<String>,
 ) -> Result<()> {
-    let mut check_file = |path: &Option<String>, name: &str| {
-        if let Some(ref p) = path {
-            let full_path = skill_dir.join(p);
-            if !full_path.exists() {
-                warnings.push(format!("{} instruction file not found: {}", name, p));
-            }
+    let files = [
+        (&instructions.core, "Core"),
+        (&instructions.claude_code, "Claude Code"),
+        (&instructions.cursor, "Cursor"),
+        (&instructions.codex, "Codex"),
+        (&instructions.windsurf, "Windsurf"),
+        (&instructions.mcp, "MCP"),
+    ];
+
+    for (path, name) in files {
+        let Some(p) = path else {
+            continue;
+        };
+        if !skill_dir.join(p).exists() {
+            warnings.push(format!("{} instruction file not found: {}", name, p));
+        } else if skill_file(skill_dir, p).is_none() {
+            bail!(
+                "{} instruction file must be inside the skill directory: {}",
+                name,
+                p
+            );
         }
-    };
-
-    check_file(&instructions.core, "Core");
-    check_file(&instructions.claude_code, "Claude Code");
-    check_file(&instructions.cursor, "Cursor");
-    check_file(&instructions.codex, "Codex");
-    check_file(&instructions.windsurf, "Windsurf");
-    check_file(&instructions.mcp, "MCP");
+    }
 
     Ok(())
 }
@@ -493,6 +511,12 @@ fn validate_workflows(
                 "Workflow '{}' file not found: {}",
                 name, workflow.file
             ));
+        } else if skill_file(skill_dir, &workflow.file).is_none() {
+            bail!(
+                "Workflow '{}' file must be inside the skill directory: {}",
+                name,
+                workflow.file
+            );
         }
     }
     Ok(())
@@ -541,3 +565,26 @@ fn validate_auth(auth: &AuthConfig) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skill_file_stays_inside_skill_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let skill_dir = root.path().join("skill");
+        fs::create_dir_all(skill_dir.join("instructions")).unwrap();
+        fs::write(skill_dir.join("instructions/mcp.md"), "guide").unwrap();
+        let secret = root.path().join("id_rsa");
+        fs::write(&secret, "key").unwrap();
+        std::os::unix::fs::symlink(&secret, skill_dir.join("link.md")).unwrap();
+
+        assert!(skill_file(&skill_dir, "instructions/mcp.md").is_some());
+        assert!(skill_file(&skill_dir, "./instructions/../instructions/mcp.md").is_some());
+        assert!(skill_file(&skill_dir, "../id_rsa").is_none());
+        assert!(skill_file(&skill_dir, secret.to_str().unwrap()).is_none());
+        assert!(skill_file(&skill_dir, "link.md").is_none());
+        assert!(skill_file(&skill_dir, "missing.md").is_none());
+    }
+}
@@ -6,7 +6,7 @@ use std::fs;
 use std::path::Path;
 use std::process::Command;
 
-use super::{fgp_services_dir, service_socket_path};
+use super::{fgp_services_dir, service, service_socket_path};
 
 pub fn run(service: &str, foreground: bool) -> Result<()> {
     let service_dir = fgp_services_dir().join(service);
@@ -20,6 +20,11 @@ pub fn run(service: &str, foreground: bool) -> Result<()> {
         );
     }
 
+    // Delegate to systemd when a user unit manages this daemon
+    if !foreground && service::has_unit(service) {
+        return start_with_systemd(service);
+    }
+
     // Check if already running
     let socket_path = service_socket_path(service);
     if socket_path.exists() {
@@ -96,6 +101,36 @@ pub fn run(service: &str, foreground: bool) -> Result<()> {
     Ok(())
 }
 
+/// Start a daemon through its systemd user unit.
+fn start_with_systemd(name: &str) -> Result<()> {
+    let unit = service::unit_name(name);
+    println!(
+        "{} Starting {} via systemd ({})...",
+        "→".blue().bold(),
+        name.bold(),
+        unit
+    );
+
+    service::systemctl(&["start", &unit])?;
+
+    // Wait a moment for socket to appear
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    let socket_path = service_socket_path(name);
+    if socket_path.exists() {
+        println!("{} {} started", "✓".green().bold(), name.bold());
+        println!("  Socket: {}", socket_path.display().to_string().dimmed());
+    } else {
+        println!(
+            "{} Unit started but socket not found yet. Check 'journalctl --user -u {}'.",
+            "?".yellow().bold(),
+            unit
+        );
+    }
+
+    Ok(())
+}
+
 /// Check if a path looks like a valid FGP service directory.
 #[allow(dead_code)]
 pub fn is_valid_service_dir(path: &Path) -> bool {
@@ -5,7 +5,7 @@ use colored::Colorize;
 use std::fs;
 use tabled::{Table, Tabled};
 
-use super::{fgp_services_dir, service_socket_path};
+use super::{fgp_services_dir, service, service_socket_path};
 
 #[derive(Tabled)]
 struct ServiceStatus {
@@ -66,6 +66,7 @@ pub fn run(verbose: bool) -> Result<()> {
                         let status_colored = match status_str {
                             "healthy" => "● running".green().to_string(),
                             "degraded" => "◐ degraded".yellow().to_string(),
+                            "idle" => "○ idle".dimmed().to_string(),
                             _ => format!("● {}", status_str).green().to_string(),
                         };
 
@@ -83,6 +84,16 @@ pub fn run(verbose: bool) -> Result<()> {
                     "-".to_string(),
                 ),
             }
+        } else if service::has_unit(service_name) {
+            // Report the systemd unit state (e.g. "activating", "failed")
+            let state = service::unit_state(service_name);
+            let status = format!("○ {} (systemd)", state);
+            let status_colored = if state == "failed" {
+                status.red().to_string()
+            } else {
+                status.dimmed().to_string()
+            };
+            (status_colored, "-".to_string(), "-".to_string())
         } else {
             (
                 "○ stopped".dimmed().to_string(),
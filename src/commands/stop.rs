@@ -3,9 +3,23 @@
 use anyhow::{bail, Result};
 use colored::Colorize;
 
-use super::service_socket_path;
+use super::{service, service_socket_path};
 
 pub fn run(service: &str) -> Result<()> {
+    // Delegate to systemd when a user unit manages this daemon
+    if service::has_unit(service) {
+        let unit = service::unit_name(service);
+        println!(
+            "{} Stopping {} via systemd ({})...",
+            "→".blue().bold(),
+            service.bold(),
+            unit
+        );
+        service::systemctl(&["stop", &unit])?;
+        println!("{} {} stopped.", "✓".green().bold(), service.bold());
+        return Ok(());
+    }
+
     let socket_path = service_socket_path(service);
 
     if !socket_path.exists() {
@@ -0,0 +1,498 @@
+//! Collect diagnostics into a tarball for bug reports.
+//!
+//! The bundle holds manifests, recent logs, status and health output, skill
+//! and tap tracking files, and the fgp version and OS. Known secrets (license
+//! keys, env file values, OAuth tokens, secret-looking environment variables)
+//! are redacted before anything is written.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+use super::skill::installed_skills_path;
+use super::skill_tap::taps_config_path;
+use super::{fgp_services_dir, installed_services, license, logs, service, service_socket_path};
+
+/// Replacement for redacted values.
+const REDACTED: &str = "[REDACTED]";
+
+/// Shorter values are too likely to appear by chance to scrub from free text.
+const MIN_SECRET_LEN: usize = 6;
+
+/// Object keys whose values are treated as secrets.
+const SECRET_KEY_PATTERN: &str = r"(?i)(token|secret|password|passwd|api[_-]?key|license[_-]?key|private[_-]?key|credential|bearer)";
+
+/// Credentials in an `Authorization` header, or a long token after "Bearer".
+/// Plain prose like "basic auth configured" is left alone.
+const AUTH_PATTERN: &str = r#"(?i)(authorization["']?\s*[:=]\s*["']?(?:bearer|basic)\s+)[A-Za-z0-9._~+/=-]+|(\bbearer\s+)[A-Za-z0-9._~+/-]{20,}=*"#;
+
+/// Collects known secret values and scrubs them from bundle contents.
+struct Redactor {
+    secret_key: Regex,
+    auth: Regex,
+    /// Longest first, so a secret containing another is replaced whole
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    fn new() -> Self {
+        Self {
+            secret_key: Regex::new(SECRET_KEY_PATTERN).expect("valid regex"),
+            auth: Regex::new(AUTH_PATTERN).expect("valid regex"),
+            secrets: Vec::new(),
+        }
+    }
+
+    /// Remember a secret value.
+    fn add(&mut self, secret: &str) {
+        let secret = secret.trim();
+        if secret.len() >= MIN_SECRET_LEN && !self.secrets.iter().any(|s| s == secret) {
+            self.secrets.push(secret.to_string());
+            self.secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        }
+    }
+
+    /// Whether an object key or variable name looks like it holds a secret.
+    fn is_secret_key(&self, key: &str) -> bool {
+        // Paths to secret files aren't secrets themselves
+        let lower = key.to_ascii_lowercase();
+        if lower.ends_with("path") || lower.ends_with("file") {
+            return false;
+        }
+        self.secret_key.is_match(key)
+    }
+
+    /// Remember every string value under secret-looking keys.
+    fn add_from_json(&mut self, value: &Value) {
+        match value {
+            Value::Object(obj) => {
+                for (key, v) in obj {
+                    match v {
+                        Value::String(s) if self.is_secret_key(key) => self.add(s),
+                        _ => self.add_from_json(v),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|v| self.add_from_json(v)),
+            _ => {}
+        }
+    }
+
+    /// Scrub known secrets and auth headers from text.
+    fn text(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for secret in &self.secrets {
+            out = out.replace(secret.as_str(), REDACTED);
+        }
+        self.auth
+            .replace_all(&out, format!("${{1}}${{2}}{}", REDACTED))
+            .into_owned()
+    }
+
+    /// Redact secret-looking keys in a JSON value, then scrub known secrets.
+    fn json(&self, value: &Value) -> Value {
+        let value = self.mask_keys(value);
+        let text = self.text(&value.to_string());
+        serde_json::from_str(&text).unwrap_or(value)
+    }
+
+    fn mask_keys(&self, value: &Value) -> Value {
+        match value {
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(key, v)| {
+                        let masked = match v {
+                            Value::String(_) | Value::Number(_) if self.is_secret_key(key) => {
+                                Value::String(REDACTED.to_string())
+                            }
+                            _ => self.mask_keys(v),
+                        };
+                        (key.clone(), masked)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.mask_keys(v)).collect()),
+            other => other.clone(),
+        }
+    }
+}
+
+/// Parse `KEY=value` lines from an env file.
+fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| {
+            let l = l.strip_prefix("export ").unwrap_or(l);
+            let (key, value) = l.split_once('=')?;
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Files added to the archive, relative to its root directory.
+struct Bundle {
+    root: String,
+    builder: tar::Builder<GzEncoder<File>>,
+    files: usize,
+}
+
+impl Bundle {
+    fn add(&mut self, name: &str, content: &[u8]) -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+
+        self.builder
+            .append_data(&mut header, format!("{}/{}", self.root, name), content)
+            .with_context(|| format!("Failed to add {} to bundle", name))?;
+        self.files += 1;
+        Ok(())
+    }
+
+    fn add_json(&mut self, name: &str, value: &Value) -> Result<()> {
+        let content = serde_json::to_string_pretty(value)?;
+        self.add(name, content.as_bytes())
+    }
+}
+
+/// Write a support bundle for the given services (all installed if empty).
+pub fn run(services: &[String], output: Option<&str>, log_lines: usize) -> Result<()> {
+    let services = if services.is_empty() {
+        installed_services()?
+    } else {
+        for service in services {
+            if !fgp_services_dir().join(service).is_dir() {
+                bail!("Service '{}' is not installed", service);
+            }
+        }
+        services.to_vec()
+    };
+
+    let stamp = Utc::now().format("%Y%m%d-%H%M%S").to_string();
+    let root = format!("fgp-support-{}", stamp);
+    let output = output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", root)));
+
+    println!("{} Collecting support bundle...", "→".blue().bold());
+
+    let redactor = collect_secrets(&services);
+
+    let file =
+        File::create(&output).with_context(|| format!("Failed to create {}", output.display()))?;
+    let mut bundle = Bundle {
+        root,
+        builder: tar::Builder::new(GzEncoder::new(file, Compression::default())),
+        files: 0,
+    };
+
+    bundle.add_json("system.json", &system_info())?;
+
+    let mut statuses = Vec::new();
+    for service in &services {
+        let status = add_service(&mut bundle, service, log_lines, &redactor)?;
+        statuses.push(status);
+        println!("  {} {}", "✓".green(), service);
+    }
+    bundle.add_json("status.json", &Value::Array(statuses))?;
+
+    for (name, path) in [
+        ("skills/installed_skills.json", installed_skills_path()),
+        ("taps/taps.json", taps_config_path()),
+    ] {
+        add_config_file(&mut bundle, name, &path, &redactor)?;
+    }
+
+    let files = bundle.files;
+    bundle
+        .builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .context("Failed to finish bundle")?;
+
+    let size = fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+    println!();
+    println!(
+        "{} Wrote {} ({} files, {:.1} KB)",
+        "✓".green().bold(),
+        output.display().to_string().bold(),
+        files,
+        size as f64 / 1024.0
+    );
+    println!(
+        "  {}",
+        "Secrets were redacted automatically; review the bundle before sharing it.".dimmed()
+    );
+
+    Ok(())
+}
+
+/// Gather secret values to redact: license keys, env files, OAuth tokens and
+/// secret-looking environment variables.
+fn collect_secrets(services: &[String]) -> Redactor {
+    let mut redactor = Redactor::new();
+
+    if let Ok(cache) = license::load_license_cache() {
+        for cached in &cache.licenses {
+            redactor.add(&cached.license_key);
+        }
+    }
+
+    for (key, value) in std::env::vars() {
+        if redactor.is_secret_key(&key) {
+            redactor.add(&value);
+        }
+    }
+
+    for service in services {
+        for path in env_files(service) {
+            if let Ok(content) = fs::read_to_string(&path) {
+                for (_, value) in parse_env_file(&content) {
+                    redactor.add(&value);
+                }
+            }
+        }
+
+        // OAuth token and client secret files declared in the manifest
+        let dir = fgp_services_dir().join(service);
+        let Some(manifest) = read_json(&dir.join("manifest.json")) else {
+            continue;
+        };
+        for key in ["token_path", "credentials_path"] {
+            if let Some(path) = manifest["auth"][key].as_str().filter(|p| !p.is_empty()) {
+                if let Some(tokens) = read_json(&service::resolve(&dir, path)) {
+                    redactor.add_from_json(&tokens);
+                }
+            }
+        }
+        redactor.add_from_json(&manifest);
+    }
+
+    redactor
+}
+
+/// Env files a service's daemon reads.
+fn env_files(service: &str) -> Vec<PathBuf> {
+    let mut files = vec![fgp_services_dir().join(service).join(".env")];
+    if let Some(env_file) = service::spec_from_manifest(service)
+        .ok()
+        .and_then(|s| s.env_file)
+    {
+        if !files.contains(&env_file) {
+            files.push(env_file);
+        }
+    }
+    files.retain(|f| f.exists());
+    files
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Add one service's manifest, env keys, logs and health, returning its status.
+fn add_service(
+    bundle: &mut Bundle,
+    service: &str,
+    log_lines: usize,
+    redactor: &Redactor,
+) -> Result<Value> {
+    let dir = fgp_services_dir().join(service);
+    let prefix = format!("services/{}", service);
+
+    let manifest_path = dir.join("manifest.json");
+    add_config_file(
+        bundle,
+        &format!("{}/manifest.json", prefix),
+        &manifest_path,
+        redactor,
+    )?;
+
+    // Only variable names; every value is replaced
+    for path in env_files(service) {
+        if let Ok(content) = fs::read_to_string(&path) {
+            let redacted: String = parse_env_file(&content)
+                .into_iter()
+                .map(|(key, _)| format!("{}={}\n", key, REDACTED))
+                .collect();
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| ".env".to_string());
+            bundle.add(&format!("{}/{}", prefix, name), redacted.as_bytes())?;
+        }
+    }
+
+    match logs::recent_lines(service, log_lines) {
+        Ok(lines) if !lines.is_empty() => {
+            let text = redactor.text(&(lines.join("\n") + "\n"));
+            bundle.add(&format!("{}/daemon.log", prefix), text.as_bytes())?;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            let note = format!("Failed to read logs: {}\n", e);
+            bundle.add(&format!("{}/daemon.log.error", prefix), note.as_bytes())?;
+        }
+    }
+
+    let health = health_json(service);
+    bundle.add_json(&format!("{}/health.json", prefix), &redactor.json(&health))?;
+
+    let mut status = json!({
+        "service": service,
+        "manifest": manifest_path.exists(),
+        "socket": service_socket_path(service).exists(),
+        "running": health["ok"].as_bool().unwrap_or(false),
+        "status": health["result"]["status"].clone(),
+        "version": health["result"]["version"].clone(),
+    });
+    if service::has_unit(service) {
+        status["systemd"] = json!(service::unit_state(service));
+    }
+
+    Ok(status)
+}
+
+/// Add a config file, redacted, or a note if it can't be parsed.
+fn add_config_file(
+    bundle: &mut Bundle,
+    name: &str,
+    path: &Path,
+    redactor: &Redactor,
+) -> Result<()> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+
+    match serde_json::from_str::<Value>(&content) {
+        Ok(value) => bundle.add_json(name, &redactor.json(&value)),
+        // Keep corrupt files; they are often the bug
+        Err(_) => bundle.add(name, redactor.text(&content).as_bytes()),
+    }
+}
+
+/// Query a service's health endpoint.
+pub fn health_json(service: &str) -> Value {
+    let socket = service_socket_path(service);
+    if !socket.exists() {
+        return json!({ "ok": false, "error": "not running (no socket)" });
+    }
+
+    let client = match fgp_daemon::FgpClient::new(&socket) {
+        Ok(c) => c,
+        Err(e) => return json!({ "ok": false, "error": format!("connect failed: {}", e) }),
+    };
+
+    let start = Instant::now();
+    match client.health() {
+        Ok(response) => {
+            let error = response
+                .error
+                .map(|e| json!({ "code": e.code, "message": e.message }));
+            json!({
+                "ok": response.ok,
+                "result": response.result,
+                "error": error,
+                "latency_ms": start.elapsed().as_secs_f64() * 1000.0,
+            })
+        }
+        Err(e) => json!({ "ok": false, "error": format!("health failed: {}", e) }),
+    }
+}
+
+/// fgp version and OS details.
+fn system_info() -> Value {
+    json!({
+        "fgp_version": env!("CARGO_PKG_VERSION"),
+        "generated_at": Utc::now().to_rfc3339(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "os_release": os_release(),
+        "kernel": command_output("uname", &["-sr"]),
+    })
+}
+
+/// Human-readable OS name and version.
+fn os_release() -> Option<String> {
+    if cfg!(target_os = "macos") {
+        let name = command_output("sw_vers", &["-productName"])?;
+        let version = command_output("sw_vers", &["-productVersion"]).unwrap_or_default();
+        return Some(format!("{} {}", name, version).trim().to_string());
+    }
+
+    let content = fs::read_to_string("/etc/os-release").ok()?;
+    content.lines().find_map(|l| {
+        l.strip_prefix("PRETTY_NAME=")
+            .map(|v| v.trim_matches('"').to_string())
+    })
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_known_secrets_and_secret_keys() {
+        let mut redactor = Redactor::new();
+        for (_, value) in parse_env_file("export GMAIL_TOKEN=\"ya29.abcdef\"\n# comment\nDEBUG=1\n")
+        {
+            redactor.add(&value);
+        }
+
+        let log = redactor.text("sending ya29.abcdef with Authorization: Bearer xyz.123");
+        assert_eq!(
+            log,
+            "sending [REDACTED] with Authorization: Bearer [REDACTED]"
+        );
+
+        let manifest = json!({
+            "name": "gmail",
+            "auth": { "client_secret": "s3cr3t-value", "token_path": "~/.fgp/token.json" },
+            "note": "uses ya29.abcdef",
+        });
+        let redacted = redactor.json(&manifest);
+        assert_eq!(redacted["auth"]["client_secret"], REDACTED);
+        assert_eq!(redacted["auth"]["token_path"], "~/.fgp/token.json");
+        assert_eq!(redacted["note"], "uses [REDACTED]");
+        assert_eq!(redacted["name"], "gmail");
+    }
+
+    #[test]
+    fn test_auth_redaction_leaves_prose_alone() {
+        let redactor = Redactor::new();
+
+        let prose = "basic auth configured; bearer tokens refresh hourly";
+        assert_eq!(redactor.text(prose), prose);
+
+        assert_eq!(
+            redactor.text(r#"{"Authorization":"Basic dXNlcjpwYXNz"}"#),
+            r#"{"Authorization":"Basic [REDACTED]"}"#
+        );
+        assert_eq!(
+            redactor.text("retrying with Bearer eyJhbGciOiJIUzI1NiJ9.e30.sig"),
+            "retrying with Bearer [REDACTED]"
+        );
+    }
+}
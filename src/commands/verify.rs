@@ -0,0 +1,324 @@
+//! Check that a daemon implements the methods its manifest declares.
+//!
+//! Exits non-zero on drift, so package authors can run it in CI before
+//! publishing.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::methods::{self, MethodSpec, BUILTIN_METHODS};
+use super::service_socket_path;
+
+/// How long to wait for a daemon we started to answer.
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A difference between the manifest and the live daemon.
+#[derive(Debug, PartialEq)]
+enum Drift {
+    /// Declared in the manifest but not served by the daemon
+    MissingMethod(String),
+    /// Served by the daemon but not declared in the manifest
+    UndocumentedMethod(String),
+    MissingParam {
+        method: String,
+        param: String,
+    },
+    UndocumentedParam {
+        method: String,
+        param: String,
+    },
+    TypeMismatch {
+        method: String,
+        param: String,
+        manifest: String,
+        daemon: String,
+    },
+    RequiredMismatch {
+        method: String,
+        param: String,
+        manifest: bool,
+        daemon: bool,
+    },
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let required = |r: &bool| if *r { "required" } else { "optional" };
+        match self {
+            Drift::MissingMethod(m) => {
+                write!(f, "{}: declared in manifest but not served by daemon", m)
+            }
+            Drift::UndocumentedMethod(m) => {
+                write!(f, "{}: served by daemon but missing from manifest", m)
+            }
+            Drift::MissingParam { method, param } => {
+                write!(
+                    f,
+                    "{}({}): param declared in manifest but not accepted by daemon",
+                    method, param
+                )
+            }
+            Drift::UndocumentedParam { method, param } => {
+                write!(
+                    f,
+                    "{}({}): param accepted by daemon but missing from manifest",
+                    method, param
+                )
+            }
+            Drift::TypeMismatch {
+                method,
+                param,
+                manifest,
+                daemon,
+            } => write!(
+                f,
+                "{}({}): type is {} in manifest but {} in daemon",
+                method, param, manifest, daemon
+            ),
+            Drift::RequiredMismatch {
+                method,
+                param,
+                manifest,
+                daemon,
+            } => write!(
+                f,
+                "{}({}): {} in manifest but {} in daemon",
+                method,
+                param,
+                required(manifest),
+                required(daemon)
+            ),
+        }
+    }
+}
+
+/// Compare manifest methods against the daemon's.
+fn diff_contract(declared: &[MethodSpec], live: &[MethodSpec]) -> Vec<Drift> {
+    let mut drifts = Vec::new();
+
+    for method in declared {
+        let Some(served) = live.iter().find(|m| m.name == method.name) else {
+            drifts.push(Drift::MissingMethod(method.name.clone()));
+            continue;
+        };
+
+        for param in &method.params {
+            let Some(accepted) = served.params.iter().find(|p| p.name == param.name) else {
+                drifts.push(Drift::MissingParam {
+                    method: method.name.clone(),
+                    param: param.name.clone(),
+                });
+                continue;
+            };
+
+            if !methods::same_type(&param.param_type, &accepted.param_type) {
+                drifts.push(Drift::TypeMismatch {
+                    method: method.name.clone(),
+                    param: param.name.clone(),
+                    manifest: param.param_type.clone(),
+                    daemon: accepted.param_type.clone(),
+                });
+            }
+
+            if param.required != accepted.required {
+                drifts.push(Drift::RequiredMismatch {
+                    method: method.name.clone(),
+                    param: param.name.clone(),
+                    manifest: param.required,
+                    daemon: accepted.required,
+                });
+            }
+        }
+
+        for accepted in &served.params {
+            if !method.params.iter().any(|p| p.name == accepted.name) {
+                drifts.push(Drift::UndocumentedParam {
+                    method: method.name.clone(),
+                    param: accepted.name.clone(),
+                });
+            }
+        }
+    }
+
+    for served in live {
+        if BUILTIN_METHODS.contains(&served.name.as_str()) {
+            continue;
+        }
+        if !declared.iter().any(|m| m.name == served.name) {
+            drifts.push(Drift::UndocumentedMethod(served.name.clone()));
+        }
+    }
+
+    drifts
+}
+
+/// Whether the daemon answers on its socket.
+fn is_running(service: &str) -> bool {
+    let socket_path = service_socket_path(service);
+    socket_path.exists()
+        && fgp_daemon::FgpClient::new(&socket_path)
+            .map(|c| c.is_running())
+            .unwrap_or(false)
+}
+
+/// Start the daemon and wait until it answers.
+fn start_and_wait(service: &str) -> Result<()> {
+    super::start::run(service, false)?;
+
+    let deadline = Instant::now() + START_TIMEOUT;
+    while !is_running(service) {
+        if Instant::now() >= deadline {
+            bail!(
+                "'{}' didn't answer within {}s. Check 'fgp logs {}'.",
+                service,
+                START_TIMEOUT.as_secs(),
+                service
+            );
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+
+    Ok(())
+}
+
+/// Verify a service's manifest against its live daemon (`fgp verify <service>`).
+pub fn run(service: &str) -> Result<()> {
+    let manifest_path = methods::installed_manifest(service);
+    if !manifest_path.exists() {
+        bail!(
+            "Service '{}' is not installed. Run 'fgp install <path>' first.",
+            service
+        );
+    }
+
+    let declared = methods::manifest_methods(&manifest_path)?;
+    if declared.is_empty() {
+        bail!("{} declares no methods", manifest_path.display());
+    }
+
+    let started = !is_running(service);
+    if started {
+        if let Err(e) = start_and_wait(service) {
+            // It may have come up too slowly; don't leave it behind
+            let _ = super::stop::run(service);
+            return Err(e);
+        }
+    }
+
+    let live = methods::daemon_methods(service);
+
+    // Leave the daemon as we found it
+    if started {
+        super::stop::run(service)?;
+    }
+    let live = live?;
+
+    println!();
+    let drifts = diff_contract(&declared, &live);
+    if drifts.is_empty() {
+        println!(
+            "{} {} matches its manifest ({} methods)",
+            "✓".green().bold(),
+            service.bold(),
+            declared.len()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} {} has drifted from its manifest:",
+        "✗".red().bold(),
+        service.bold()
+    );
+    println!();
+    for drift in &drifts {
+        println!("  {} {}", "✗".red(), drift);
+    }
+    println!();
+
+    bail!(
+        "{} difference(s) between manifest.json and the running daemon",
+        drifts.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_contract() {
+        let declared = methods::parse_methods(&json!([
+            {"name": "a.send", "params": [
+                {"name": "to", "type": "string", "required": true},
+                {"name": "limit", "type": "int", "required": false},
+                {"name": "cc", "type": "array"}
+            ]},
+            {"name": "a.gone"}
+        ]));
+        let live = methods::parse_methods(&json!([
+            {"name": "health"},
+            {"name": "a.send", "params": [
+                {"name": "to", "type": "string", "required": false},
+                {"name": "limit", "type": "integer", "required": false},
+                {"name": "bcc", "type": "array"}
+            ]},
+            {"name": "a.new"}
+        ]));
+
+        let drifts = diff_contract(&declared, &live);
+        assert_eq!(
+            drifts,
+            vec![
+                Drift::RequiredMismatch {
+                    method: "a.send".into(),
+                    param: "to".into(),
+                    manifest: true,
+                    daemon: false,
+                },
+                Drift::MissingParam {
+                    method: "a.send".into(),
+                    param: "cc".into(),
+                },
+                Drift::UndocumentedParam {
+                    method: "a.send".into(),
+                    param: "bcc".into(),
+                },
+                Drift::MissingMethod("a.gone".into()),
+                Drift::UndocumentedMethod("a.new".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_contract_sees_through_rust_generics() {
+        let declared = methods::parse_methods(&json!([
+            {"name": "a.list", "params": [
+                {"name": "labels", "type": "array"},
+                {"name": "limit", "type": "integer"},
+                {"name": "query", "type": "string"}
+            ]}
+        ]));
+        let live = methods::parse_methods(&json!([
+            {"name": "a.list", "params": [
+                {"name": "labels", "type": "Vec<String>"},
+                {"name": "limit", "type": "Option<u32>"},
+                {"name": "query", "type": "Vec<String>"}
+            ]}
+        ]));
+
+        assert_eq!(
+            diff_contract(&declared, &live),
+            vec![Drift::TypeMismatch {
+                method: "a.list".into(),
+                param: "query".into(),
+                manifest: "string".into(),
+                daemon: "Vec<String>".into(),
+            }]
+        );
+    }
+}
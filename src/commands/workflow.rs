@@ -217,7 +217,7 @@ pub fn init(template: &str) -> Result<()> {
 }
 
 /// Get the workflows directory.
-fn workflows_dir() -> PathBuf {
+pub fn workflows_dir() -> PathBuf {
     let base = shellexpand::tilde("~/.fgp/workflows");
     PathBuf::from(base.as_ref())
 }
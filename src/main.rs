@@ -14,6 +14,8 @@
 //! fgp logs <service>      # View daemon logs
 //! fgp mcp serve           # Start MCP bridge
 //! fgp monitor             # Health monitor with notifications
+//! fgp metrics serve       # Prometheus metrics exporter
+//! fgp activator           # Start daemons on demand, stop when idle
 //! ```
 
 mod commands;
@@ -40,6 +42,27 @@ enum Commands {
     /// Detect installed AI agents on this machine
     Agents,
 
+    /// Diagnose problems with the FGP installation
+    Doctor {
+        /// Apply safe fixes (remove stale sockets and dangling links, chmod entrypoints)
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Write a tar.gz of manifests, logs and health output for bug reports
+    SupportBundle {
+        /// Services to include (defaults to all installed)
+        services: Vec<String>,
+
+        /// Output file (defaults to fgp-support-<timestamp>.tar.gz)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Log lines to include per service
+        #[arg(long, default_value = "1000")]
+        log_lines: usize,
+    },
+
     /// Generate a new daemon from template (67 service presets available)
     Generate {
         #[command(subcommand)]
@@ -112,7 +135,22 @@ enum Commands {
     },
 
     /// List available methods for a service
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Methods {
+        #[command(subcommand)]
+        action: Option<MethodsAction>,
+
+        /// Service name
+        #[arg(required = true)]
+        service: Option<String>,
+
+        /// Show full parameter definitions (type, required, default, description)
+        #[arg(long)]
+        schema: bool,
+    },
+
+    /// Check that a daemon serves the methods its manifest declares
+    Verify {
         /// Service name
         service: String,
     },
@@ -121,6 +159,14 @@ enum Commands {
     Health {
         /// Service name
         service: String,
+
+        /// Show recorded health history instead of probing now
+        #[arg(long)]
+        history: bool,
+
+        /// History window (e.g., "1h", "24h", "7d")
+        #[arg(long, default_value = "24h")]
+        since: String,
     },
 
     /// Open the web dashboard
@@ -143,8 +189,13 @@ enum Commands {
 
     /// View daemon logs
     Logs {
-        /// Service name
-        service: String,
+        /// Service names (several are interleaved by timestamp)
+        #[arg(required_unless_present = "all")]
+        services: Vec<String>,
+
+        /// Show logs from all installed services
+        #[arg(long, conflicts_with = "services")]
+        all: bool,
 
         /// Follow log output (like tail -f)
         #[arg(short, long)]
@@ -153,6 +204,26 @@ enum Commands {
         /// Number of lines to show
         #[arg(short = 'n', long, default_value = "50")]
         lines: usize,
+
+        /// Minimum level to show (trace, debug, info, warn, error)
+        #[arg(short, long)]
+        level: Option<String>,
+
+        /// Show entries since a time (e.g., "1h", "2024-01-15T10:00:00Z")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Show entries until a time (e.g., "10m", "2024-01-15T12:00:00Z")
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Only show entries whose message or target matches this regex
+        #[arg(short, long)]
+        grep: Option<String>,
+
+        /// Output entries as JSON lines
+        #[arg(long)]
+        json: bool,
     },
 
     /// MCP bridge commands (expose FGP as MCP server)
@@ -182,6 +253,40 @@ enum Commands {
         /// Delay between restart attempts in seconds
         #[arg(long, default_value = "5")]
         restart_delay: u64,
+
+        /// Append state-change events as JSON lines to this file
+        #[arg(long)]
+        events_file: Option<String>,
+
+        /// Command to run on each state change (event JSON on stdin, FGP_* env vars)
+        #[arg(long)]
+        on_event: Option<String>,
+
+        /// URL to POST each state-change event to as JSON
+        #[arg(long)]
+        webhook: Option<String>,
+    },
+
+    /// Start daemons on first connection and stop them when idle
+    Activator {
+        /// Services to activate (default: all installed)
+        services: Vec<String>,
+
+        /// Stop daemons after this many seconds without requests (0 = never)
+        #[arg(long, default_value = "600")]
+        idle_timeout: u64,
+    },
+
+    /// Manage systemd user units for daemons and the monitor (Linux)
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+
+    /// Export daemon metrics in Prometheus format
+    Metrics {
+        #[command(subcommand)]
+        action: MetricsAction,
     },
 
     /// Run or validate a workflow
@@ -229,10 +334,96 @@ enum WorkflowAction {
     },
 }
 
+#[derive(Subcommand)]
+enum ServiceAction {
+    /// Generate a systemd user unit for a daemon from its manifest
+    ///
+    /// Don't combine with `fgp activator`; a unit and the activator both manage
+    /// the daemon's socket.
+    Unit {
+        /// Service name
+        service: String,
+
+        /// Write the unit to ~/.config/systemd/user and enable it
+        #[arg(long)]
+        install: bool,
+    },
+
+    /// Generate a systemd user unit for `fgp monitor`
+    MonitorUnit {
+        /// Write the unit to ~/.config/systemd/user, enable and start it
+        #[arg(long)]
+        install: bool,
+
+        /// Check interval in seconds
+        #[arg(short, long, default_value = "60")]
+        interval: u64,
+
+        /// Auto-restart crashed services
+        #[arg(short, long)]
+        auto_restart: bool,
+    },
+
+    /// Disable and remove a daemon's systemd user unit
+    Remove {
+        /// Service name
+        service: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MethodsAction {
+    /// Classify method changes between two versions and suggest a semver bump
+    Diff {
+        /// Old side: manifest path, package directory, service or service@version
+        old: String,
+
+        /// New side: manifest path, package directory, service or service@version
+        new: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MetricsAction {
+    /// Serve metrics over HTTP for Prometheus to scrape
+    Serve {
+        /// Address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:9464")]
+        listen: String,
+    },
+
+    /// Print current metrics once
+    Dump,
+}
+
 #[derive(Subcommand)]
 enum McpBridgeAction {
     /// Start MCP bridge server (stdio mode)
-    Serve,
+    Serve {
+        /// Only expose these daemons (repeatable; default: all installed)
+        #[arg(short, long = "service")]
+        services: Vec<String>,
+
+        /// Hide these daemons (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+
+        /// Serve Streamable HTTP on this address (e.g. 127.0.0.1:8931) instead of stdio
+        #[arg(long, value_name = "ADDR")]
+        http: Option<String>,
+
+        /// Bearer token HTTP clients must send
+        #[arg(long, env = "FGP_MCP_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// Requests handled at once in stdio mode
+        #[arg(long, default_value = "16")]
+        workers: usize,
+
+        /// Concurrent calls allowed to any one daemon
+        #[arg(long, default_value = "4")]
+        max_per_daemon: usize,
+    },
 
     /// Register FGP with Claude Code
     Install,
@@ -456,6 +647,12 @@ fn main() -> Result<()> {
 
     match cli.command {
         Commands::Agents => commands::agents::run(),
+        Commands::Doctor { fix } => commands::doctor::run(fix),
+        Commands::SupportBundle {
+            services,
+            output,
+            log_lines,
+        } => commands::support_bundle::run(&services, output.as_deref(), log_lines),
         Commands::Generate { action } => match action {
             GenerateAction::List => commands::generate::list(),
             GenerateAction::NewDaemon {
@@ -495,17 +692,62 @@ fn main() -> Result<()> {
             no_auto_start,
         } => commands::call::run(&method, &params, service.as_deref(), no_auto_start),
         Commands::Install { path } => commands::install::run(&path),
-        Commands::Methods { service } => commands::methods::run(&service),
-        Commands::Health { service } => commands::health::run(&service),
+        Commands::Methods {
+            action,
+            service,
+            schema,
+        } => match action {
+            Some(MethodsAction::Diff { old, new }) => commands::method_diff::run(&old, &new),
+            None => commands::methods::run(&service.unwrap_or_default(), schema),
+        },
+        Commands::Verify { service } => commands::verify::run(&service),
+        Commands::Health {
+            service,
+            history,
+            since,
+        } => commands::health::run(&service, history, &since),
         Commands::Dashboard { port, open } => commands::dashboard::run(port, open),
         Commands::Tui { poll } => commands::tui::run(poll),
         Commands::Logs {
-            service,
+            services,
+            all,
             follow,
             lines,
-        } => commands::logs::run(&service, follow, lines),
+            level,
+            since,
+            until,
+            grep,
+            json,
+        } => commands::logs::run(
+            &services,
+            all,
+            follow,
+            lines,
+            &commands::logs::LogOptions {
+                level,
+                since,
+                until,
+                grep,
+                json,
+            },
+        ),
         Commands::Mcp { action } => match action {
-            McpBridgeAction::Serve => commands::mcp_bridge::serve(),
+            McpBridgeAction::Serve {
+                services,
+                exclude,
+                http,
+                token,
+                workers,
+                max_per_daemon,
+            } => {
+                let scope = commands::mcp_bridge::Scope::new(services, exclude)?;
+                match http {
+                    Some(listen) => {
+                        commands::mcp_http::serve(scope, &listen, token, max_per_daemon)
+                    }
+                    None => commands::mcp_bridge::serve(scope, workers, max_per_daemon),
+                }
+            }
             McpBridgeAction::Install => commands::mcp_bridge::install(),
             McpBridgeAction::Tools => commands::mcp_bridge::tools(),
         },
@@ -515,7 +757,39 @@ fn main() -> Result<()> {
             auto_restart,
             max_restarts,
             restart_delay,
-        } => commands::monitor::run(interval, daemon, auto_restart, max_restarts, restart_delay),
+            events_file,
+            on_event,
+            webhook,
+        } => commands::monitor::run(
+            interval,
+            daemon,
+            auto_restart,
+            max_restarts,
+            restart_delay,
+            commands::monitor::EventHooks {
+                events_file: events_file
+                    .map(|p| std::path::PathBuf::from(shellexpand::tilde(&p).as_ref())),
+                on_event,
+                webhook,
+            },
+        ),
+        Commands::Activator {
+            services,
+            idle_timeout,
+        } => commands::activator::run(&services, idle_timeout),
+        Commands::Service { action } => match action {
+            ServiceAction::Unit { service, install } => commands::service::unit(&service, install),
+            ServiceAction::MonitorUnit {
+                install,
+                interval,
+                auto_restart,
+            } => commands::service::monitor_unit(install, interval, auto_restart),
+            ServiceAction::Remove { service } => commands::service::remove(&service),
+        },
+        Commands::Metrics { action } => match action {
+            MetricsAction::Serve { listen } => commands::metrics::serve(&listen),
+            MetricsAction::Dump => commands::metrics::dump(),
+        },
         Commands::Workflow { action } => match action {
             WorkflowAction::Run { file, verbose } => commands::workflow::run(&file, verbose),
             WorkflowAction::Validate { file } => commands::workflow::validate(&file),
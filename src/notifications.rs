@@ -1,6 +1,48 @@
 //! System notification helpers.
 //!
 //! Provides cross-platform notification support for FGP alerts.
+//!
+//! - macOS: `osascript` (Notification Center)
+//! - Linux/BSD: freedesktop notifications over D-Bus, falling back to `notify-send`
+//! - Other platforms: no-op
+
+/// Application name reported to the notification server.
+#[cfg(all(unix, not(target_os = "macos")))]
+const APP_NAME: &str = "fgp";
+
+/// Notification urgency.
+///
+/// Maps onto the freedesktop urgency hint on Linux. macOS has no equivalent,
+/// so urgency is ignored there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    /// Value accepted by `notify-send --urgency`.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn as_notify_send_arg(self) -> &'static str {
+        match self {
+            Urgency::Low => "low",
+            Urgency::Normal => "normal",
+            Urgency::Critical => "critical",
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl From<Urgency> for notify_rust::Urgency {
+    fn from(urgency: Urgency) -> Self {
+        match urgency {
+            Urgency::Low => notify_rust::Urgency::Low,
+            Urgency::Normal => notify_rust::Urgency::Normal,
+            Urgency::Critical => notify_rust::Urgency::Critical,
+        }
+    }
+}
 
 /// Escape a string for use in AppleScript string literals.
 /// Handles backslashes, double quotes, and newlines which have special meaning in AppleScript.
@@ -13,54 +55,90 @@ fn escape_applescript_string(s: &str) -> String {
 }
 
 /// Send a system notification.
+pub fn notify(title: &str, message: &str, urgency: Urgency) {
+    send(title, message, urgency, None);
+}
+
+/// Send a notification with a sound.
 ///
-/// On macOS, uses osascript to display a native notification.
-/// On other platforms, this is a no-op (could be extended with notify-rust).
-pub fn notify(title: &str, message: &str) {
-    #[cfg(target_os = "macos")]
-    {
-        // Use AppleScript with proper escaping to prevent command injection
-        let escaped_title = escape_applescript_string(title);
-        let escaped_message = escape_applescript_string(message);
-
-        let script = format!(
+/// On macOS `sound` is a system sound name (e.g. "Basso"). On Linux it is
+/// passed as the freedesktop `sound-name` hint (e.g. "dialog-warning").
+pub fn notify_with_sound(title: &str, message: &str, urgency: Urgency, sound: &str) {
+    send(title, message, urgency, Some(sound));
+}
+
+#[cfg(target_os = "macos")]
+fn send(title: &str, message: &str, _urgency: Urgency, sound: Option<&str>) {
+    // Use AppleScript with proper escaping to prevent command injection
+    let escaped_title = escape_applescript_string(title);
+    let escaped_message = escape_applescript_string(message);
+
+    let script = match sound {
+        Some(sound) => format!(
+            "display notification \"{}\" with title \"{}\" sound name \"{}\"",
+            escaped_message,
+            escaped_title,
+            escape_applescript_string(sound)
+        ),
+        None => format!(
             "display notification \"{}\" with title \"{}\"",
             escaped_message, escaped_title
-        );
+        ),
+    };
 
-        let _ = std::process::Command::new("osascript")
-            .args(["-e", &script])
-            .output();
-    }
+    let _ = std::process::Command::new("osascript")
+        .args(["-e", &script])
+        .output();
+}
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        // Could use notify-rust here for Linux/Windows support
-        let _ = (title, message); // Silence unused warnings
+#[cfg(all(unix, not(target_os = "macos")))]
+fn send(title: &str, message: &str, urgency: Urgency, sound: Option<&str>) {
+    // Prefer talking to org.freedesktop.Notifications directly; fall back to
+    // notify-send when there is no session bus (e.g. under some terminals/ssh).
+    if send_dbus(title, message, urgency, sound).is_err() {
+        send_notify_send(title, message, urgency, sound);
     }
 }
 
-/// Send a notification with a sound.
-#[allow(dead_code)]
-pub fn notify_with_sound(title: &str, message: &str, sound: &str) {
-    #[cfg(target_os = "macos")]
-    {
-        let escaped_title = escape_applescript_string(title);
-        let escaped_message = escape_applescript_string(message);
-        let escaped_sound = escape_applescript_string(sound);
-
-        let script = format!(
-            "display notification \"{}\" with title \"{}\" sound name \"{}\"",
-            escaped_message, escaped_title, escaped_sound
-        );
+/// Send a notification via the freedesktop D-Bus notification service.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn send_dbus(
+    title: &str,
+    message: &str,
+    urgency: Urgency,
+    sound: Option<&str>,
+) -> Result<(), notify_rust::error::Error> {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname(APP_NAME)
+        .summary(title)
+        .body(message)
+        .urgency(urgency.into());
 
-        let _ = std::process::Command::new("osascript")
-            .args(["-e", &script])
-            .output();
+    if let Some(sound) = sound {
+        notification.sound_name(sound);
     }
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        let _ = (title, message, sound);
+    notification.show().map(|_| ())
+}
+
+/// Send a notification by shelling out to `notify-send`.
+///
+/// Arguments are passed directly (no shell), so titles and messages need no escaping.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn send_notify_send(title: &str, message: &str, urgency: Urgency, sound: Option<&str>) {
+    let mut cmd = std::process::Command::new("notify-send");
+    cmd.args(["--app-name", APP_NAME])
+        .args(["--urgency", urgency.as_notify_send_arg()]);
+
+    if let Some(sound) = sound {
+        cmd.arg(format!("--hint=string:sound-name:{}", sound));
     }
+
+    let _ = cmd.arg("--").arg(title).arg(message).output();
+}
+
+#[cfg(not(unix))]
+fn send(title: &str, message: &str, urgency: Urgency, sound: Option<&str>) {
+    let _ = (title, message, urgency, sound); // Silence unused warnings
 }
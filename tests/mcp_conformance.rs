@@ -0,0 +1,623 @@
+//! MCP conformance tests for `fgp mcp serve`
+//!
+//! Drives the stdio bridge with raw JSON-RPC lines, and the Streamable HTTP
+//! transport with raw HTTP requests, and checks the replies against the MCP
+//! lifecycle and JSON-RPC 2.0 rules.
+
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const INITIALIZE: &str = r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"conformance","version":"1"}}}"#;
+const INITIALIZED: &str = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+
+/// Send `lines` to a fresh bridge (with no services installed) and collect
+/// every reply it writes before exiting on EOF.
+fn exchange(lines: &[&str]) -> Vec<Value> {
+    let home = tempfile::tempdir().expect("Failed to create temp HOME");
+    exchange_in(home.path(), lines)
+}
+
+/// Like [`exchange`], but with `home` as the bridge's HOME.
+fn exchange_in(home: &Path, lines: &[&str]) -> Vec<Value> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fgp"))
+        .args(["mcp", "serve"])
+        .env("HOME", home)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start fgp mcp serve");
+
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        for line in lines {
+            writeln!(stdin, "{}", line).unwrap();
+        }
+    }
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "bridge exited with an error");
+
+    String::from_utf8(output.stdout)
+        .expect("stdout should be UTF-8")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("every stdout line should be JSON"))
+        .collect()
+}
+
+/// The reply with the given id.
+fn reply(replies: &[Value], id: Value) -> &Value {
+    replies
+        .iter()
+        .find(|r| r["id"] == id)
+        .unwrap_or_else(|| panic!("no reply with id {}", id))
+}
+
+#[test]
+fn test_initialize_negotiates_requested_version() {
+    let replies = exchange(&[INITIALIZE, INITIALIZED]);
+
+    // The initialized notification gets no reply
+    assert_eq!(replies.len(), 1);
+    let result = &reply(&replies, json!(0))["result"];
+    assert_eq!(result["protocolVersion"], "2025-06-18");
+    assert_eq!(result["serverInfo"]["name"], "fgp-mcp-bridge");
+    assert!(result["capabilities"]["tools"].is_object());
+}
+
+#[test]
+fn test_initialize_supports_older_and_offers_latest_for_unknown() {
+    let replies = exchange(&[
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{}}}"#,
+    ]);
+    assert_eq!(replies[0]["result"]["protocolVersion"], "2024-11-05");
+
+    let replies = exchange(&[
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"1999-01-01","capabilities":{}}}"#,
+    ]);
+    assert_eq!(replies[0]["result"]["protocolVersion"], "2025-06-18");
+}
+
+#[test]
+fn test_ping() {
+    let replies = exchange(&[
+        r#"{"jsonrpc":"2.0","id":"before","method":"ping"}"#,
+        INITIALIZE,
+        r#"{"jsonrpc":"2.0","id":"after","method":"ping"}"#,
+    ]);
+
+    assert_eq!(reply(&replies, json!("before"))["result"], json!({}));
+    assert_eq!(reply(&replies, json!("after"))["result"], json!({}));
+}
+
+#[test]
+fn test_requests_before_initialize_are_rejected() {
+    let replies = exchange(&[r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#]);
+    assert!(replies[0]["error"]["code"].is_i64());
+    assert!(replies[0].get("result").is_none());
+}
+
+#[test]
+fn test_parse_error_does_not_stop_server() {
+    let replies = exchange(&[
+        INITIALIZE,
+        "{not json",
+        r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#,
+    ]);
+
+    assert_eq!(replies.len(), 3);
+    let parse_error = replies.iter().find(|r| r["id"].is_null()).unwrap();
+    assert_eq!(parse_error["error"]["code"], -32700);
+    assert_eq!(reply(&replies, json!(2))["result"], json!({}));
+}
+
+#[test]
+fn test_invalid_requests() {
+    let replies = exchange(&[
+        INITIALIZE,
+        r#"{"jsonrpc":"2.0","id":1}"#,
+        r#"{"id":2,"method":"ping"}"#,
+        r#"{"jsonrpc":"2.0","id":{"nested":true},"method":"ping"}"#,
+        "[]",
+    ]);
+
+    assert_eq!(replies.len(), 5);
+    for r in &replies[1..] {
+        assert_eq!(r["error"]["code"], -32600, "{}", r);
+    }
+}
+
+#[test]
+fn test_unknown_method_and_notifications() {
+    let replies = exchange(&[
+        INITIALIZE,
+        INITIALIZED,
+        r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":9}}"#,
+        r#"{"jsonrpc":"2.0","method":"notifications/unknown"}"#,
+        r#"{"jsonrpc":"2.0","id":1,"method":"resources/subscribe_everything"}"#,
+    ]);
+
+    assert_eq!(replies.len(), 2);
+    assert_eq!(reply(&replies, json!(1))["error"]["code"], -32601);
+}
+
+#[test]
+fn test_batch() {
+    // Batching exists only in 2025-03-26
+    let replies = exchange(&[
+        r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{}}}"#,
+        r#"[{"jsonrpc":"2.0","id":1,"method":"ping"},{"jsonrpc":"2.0","method":"notifications/initialized"},{"jsonrpc":"2.0","id":2,"method":"ping"}]"#,
+        r#"[{"jsonrpc":"2.0","method":"notifications/initialized"}]"#,
+    ]);
+
+    assert_eq!(replies.len(), 2);
+    let batch = replies[1]
+        .as_array()
+        .expect("batch reply should be an array");
+    assert_eq!(batch.len(), 2);
+}
+
+#[test]
+fn test_batch_rejected_after_2025_06_18() {
+    let replies = exchange(&[
+        INITIALIZE,
+        r#"[{"jsonrpc":"2.0","id":1,"method":"ping"},{"jsonrpc":"2.0","id":2,"method":"ping"}]"#,
+    ]);
+
+    assert_eq!(replies.len(), 2);
+    assert_eq!(replies[1]["error"]["code"], -32600);
+    assert_eq!(replies[1]["id"], Value::Null);
+}
+
+#[test]
+fn test_cancel_queued_request() {
+    let home = tempfile::tempdir().expect("Failed to create temp HOME");
+    let service = home.path().join(".fgp").join("services").join("slow");
+    std::fs::create_dir_all(&service).unwrap();
+    // Reading this manifest blocks the only worker until the test writes it
+    let manifest = service.join("manifest.json");
+    let made = Command::new("mkfifo").arg(&manifest).status().unwrap();
+    assert!(made.success());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fgp"))
+        .args(["mcp", "serve", "--workers", "1"])
+        .env("HOME", home.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start fgp mcp serve");
+
+    let mut stdin = child.stdin.take().unwrap();
+    for line in [
+        INITIALIZE,
+        INITIALIZED,
+        r#"{"jsonrpc":"2.0","id":1,"method":"resources/read","params":{"uri":"fgp://slow/manifest"}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#,
+        r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":2}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"ping"}"#,
+    ] {
+        writeln!(stdin, "{}", line).unwrap();
+    }
+    stdin.flush().unwrap();
+
+    // Let the bridge read the cancellation while request 2 is still queued
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    std::fs::write(&manifest, r#"{"name":"slow"}"#).unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    let replies: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(
+        reply(&replies, json!(1))["result"]["contents"][0]["text"],
+        r#"{"name":"slow"}"#
+    );
+    assert!(replies.iter().all(|r| r["id"] != 2));
+    assert_eq!(reply(&replies, json!(3))["result"], json!({}));
+}
+
+#[test]
+fn test_tools() {
+    let replies = exchange(&[
+        INITIALIZE,
+        INITIALIZED,
+        r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"fgp__nope__missing","arguments":{}}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"fgp_list_daemons","arguments":{}}}"#,
+    ]);
+
+    let tools = reply(&replies, json!(1))["result"]["tools"]
+        .as_array()
+        .unwrap();
+    for tool in tools {
+        assert!(tool["name"].is_string());
+        assert_eq!(tool["inputSchema"]["type"], "object");
+    }
+    assert!(tools.iter().any(|t| t["name"] == "fgp_list_daemons"));
+
+    assert!(reply(&replies, json!(2)).get("error").is_some());
+
+    // Non-object results are wrapped so structuredContent is always an object
+    let listed = &reply(&replies, json!(3))["result"];
+    assert!(listed.get("isError").is_none());
+    assert_eq!(listed["structuredContent"], json!({ "result": [] }));
+    assert_eq!(listed["content"][0]["type"], "text");
+}
+
+/// A bridge serving Streamable HTTP on an ephemeral port, killed on drop.
+struct HttpBridge {
+    child: std::process::Child,
+    addr: String,
+    // Held open so the bridge's later stdout writes don't hit a broken pipe
+    _stdout: std::io::BufReader<std::process::ChildStdout>,
+    _home: tempfile::TempDir,
+}
+
+impl HttpBridge {
+    fn start(token: &str) -> Self {
+        let home = tempfile::tempdir().expect("Failed to create temp HOME");
+        let mut child = Command::new(env!("CARGO_BIN_EXE_fgp"))
+            .args(["mcp", "serve", "--http", "127.0.0.1:0", "--token", token])
+            .env("HOME", home.path())
+            .env("NO_COLOR", "1")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start fgp mcp serve --http");
+
+        let mut stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+        let mut banner = String::new();
+        stdout.read_line(&mut banner).unwrap();
+        let addr = banner
+            .split("http://")
+            .nth(1)
+            .and_then(|rest| rest.split('/').next())
+            .expect("banner should include the address")
+            .to_string();
+
+        Self {
+            child,
+            addr,
+            _stdout: stdout,
+            _home: home,
+        }
+    }
+
+    /// Send a request and return (status line, headers, body).
+    fn request(
+        &self,
+        method: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> (String, Vec<String>, String) {
+        let mut stream = std::net::TcpStream::connect(&self.addr).unwrap();
+        let mut request = format!(
+            "{} /mcp HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\n",
+            method,
+            self.addr,
+            body.len()
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        write!(stream, "{}\r\n{}", request, body).unwrap();
+
+        let mut response = String::new();
+        std::io::Read::read_to_string(&mut stream, &mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let mut lines = head.lines().map(String::from);
+        let status = lines.next().unwrap();
+        (status, lines.collect(), body.to_string())
+    }
+}
+
+impl Drop for HttpBridge {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn header<'a>(headers: &'a [String], name: &str) -> Option<&'a str> {
+    headers.iter().find_map(|h| {
+        let (n, v) = h.split_once(':')?;
+        n.eq_ignore_ascii_case(name).then(|| v.trim())
+    })
+}
+
+#[test]
+fn test_http_session_lifecycle() {
+    let bridge = HttpBridge::start("s3cret");
+    let auth = ("Authorization", "Bearer s3cret");
+    let accept = ("Accept", "application/json, text/event-stream");
+
+    let (status, headers, _) = bridge.request("POST", &[accept], INITIALIZE);
+    assert!(status.contains("401"), "{}", status);
+    assert!(header(&headers, "WWW-Authenticate").is_some());
+
+    let (status, _, _) = bridge.request(
+        "POST",
+        &[auth, accept, ("Origin", "https://evil.example")],
+        INITIALIZE,
+    );
+    assert!(status.contains("403"), "{}", status);
+
+    // Initialize gets a session ID
+    let (status, headers, body) = bridge.request("POST", &[auth, accept], INITIALIZE);
+    assert!(status.contains("200"), "{}", status);
+    let session = header(&headers, "Mcp-Session-Id")
+        .expect("initialize should assign a session")
+        .to_string();
+    let event = body
+        .lines()
+        .find_map(|l| l.strip_prefix("data: "))
+        .expect("SSE data line");
+    let result: Value = serde_json::from_str(event).unwrap();
+    assert_eq!(result["result"]["protocolVersion"], "2025-06-18");
+
+    let session_header = ("Mcp-Session-Id", session.as_str());
+    let version = ("MCP-Protocol-Version", "2025-06-18");
+
+    // Notifications are accepted without a body
+    let (status, _, _) = bridge.request("POST", &[auth, session_header, version], INITIALIZED);
+    assert!(status.contains("202"), "{}", status);
+
+    // Plain JSON when the client doesn't accept SSE
+    let (status, headers, body) = bridge.request(
+        "POST",
+        &[
+            auth,
+            session_header,
+            version,
+            ("Accept", "application/json"),
+        ],
+        r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+    );
+    assert!(status.contains("200"), "{}", status);
+    assert_eq!(header(&headers, "Content-Type"), Some("application/json"));
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["id"], 1);
+
+    // Requests without a session, or with an unknown one, are refused
+    let ping = r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#;
+    let (status, _, _) = bridge.request("POST", &[auth, accept], ping);
+    assert!(status.contains("400"), "{}", status);
+    let (status, _, _) = bridge.request("POST", &[auth, accept, ("Mcp-Session-Id", "nope")], ping);
+    assert!(status.contains("404"), "{}", status);
+
+    let (status, _, body) = bridge.request("POST", &[auth, session_header], "{oops");
+    assert!(status.contains("400"), "{}", status);
+    assert_eq!(
+        serde_json::from_str::<Value>(&body).unwrap()["error"]["code"],
+        -32700
+    );
+
+    // 2025-06-18 has no batching
+    let (status, _, body) = bridge.request(
+        "POST",
+        &[auth, accept, session_header, version],
+        &format!("[{}]", ping),
+    );
+    assert!(status.contains("400"), "{}", status);
+    assert_eq!(
+        serde_json::from_str::<Value>(&body).unwrap()["error"]["code"],
+        -32600
+    );
+
+    // DELETE ends the session
+    let (status, _, _) = bridge.request("DELETE", &[auth, session_header], "");
+    assert!(status.contains("204"), "{}", status);
+    let (status, _, _) = bridge.request("POST", &[auth, accept, session_header], ping);
+    assert!(status.contains("404"), "{}", status);
+}
+
+#[test]
+fn test_tools_list_changed_when_services_change() {
+    let home = tempfile::tempdir().expect("Failed to create temp HOME");
+    let services = home.path().join(".fgp").join("services");
+    std::fs::create_dir_all(&services).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fgp"))
+        .args(["mcp", "serve"])
+        .env("HOME", home.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start fgp mcp serve");
+    let mut stdin = child.stdin.take().unwrap();
+    let (lines, received) = std::sync::mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout)
+            .lines()
+            .map_while(Result::ok)
+        {
+            let _ = lines.send(serde_json::from_str::<Value>(&line).unwrap());
+        }
+    });
+
+    writeln!(stdin, "{}", INITIALIZE).unwrap();
+    writeln!(stdin, "{}", INITIALIZED).unwrap();
+    let initialized = received
+        .recv_timeout(std::time::Duration::from_secs(10))
+        .unwrap();
+    assert!(
+        initialized["result"]["capabilities"]["tools"]["listChanged"]
+            .as_bool()
+            .unwrap()
+    );
+
+    // Installing a service changes the catalogue
+    std::fs::create_dir_all(services.join("newsvc")).unwrap();
+    let notification = received
+        .recv_timeout(std::time::Duration::from_secs(10))
+        .expect("expected notifications/tools/list_changed");
+    assert_eq!(notification["method"], "notifications/tools/list_changed");
+    assert!(notification.get("id").is_none());
+
+    drop(stdin);
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn test_prompts_and_resources() {
+    let home = tempfile::tempdir().expect("Failed to create temp HOME");
+    let fgp = home.path().join(".fgp");
+
+    // An installed service with a manifest and some logs
+    let service = fgp.join("services").join("demo");
+    std::fs::create_dir_all(service.join("logs")).unwrap();
+    std::fs::write(service.join("manifest.json"), r#"{"name":"demo"}"#).unwrap();
+    std::fs::write(service.join("logs").join("daemon.log"), "started\nready\n").unwrap();
+
+    // An installed skill that uses it, with MCP instructions
+    let install = fgp.join("skills").join("installed").join("demo-skill");
+    let source = install.join("source");
+    std::fs::create_dir_all(&source).unwrap();
+    std::fs::write(
+        source.join("skill.yaml"),
+        "name: demo-skill\nversion: 1.0.0\ndescription: Demo things\nauthor: test\n\
+         daemons:\n  - name: demo\ninstructions:\n  mcp: MCP.md\n",
+    )
+    .unwrap();
+    std::fs::write(source.join("MCP.md"), "Use the demo daemon.").unwrap();
+    let installed = json!({
+        "version": 1,
+        "skills": { "demo-skill@test": [{
+            "scope": "tap",
+            "installPath": install,
+            "version": "1.0.0",
+            "installedAt": "2026-01-01T00:00:00Z",
+            "lastUpdated": "2026-01-01T00:00:00Z",
+            "gitCommitSha": null,
+            "binaryPath": null
+        }]}
+    });
+    std::fs::write(
+        fgp.join("skills").join("installed_skills.json"),
+        installed.to_string(),
+    )
+    .unwrap();
+
+    let replies = exchange_in(
+        home.path(),
+        &[
+            INITIALIZE,
+            INITIALIZED,
+            r#"{"jsonrpc":"2.0","id":1,"method":"prompts/list"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"prompts/get","params":{"name":"demo-skill","arguments":{"task":"say hi"}}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"prompts/get","params":{"name":"missing"}}"#,
+            r#"{"jsonrpc":"2.0","id":4,"method":"resources/list"}"#,
+            r#"{"jsonrpc":"2.0","id":5,"method":"resources/read","params":{"uri":"fgp://demo/manifest"}}"#,
+            r#"{"jsonrpc":"2.0","id":6,"method":"resources/read","params":{"uri":"fgp://demo/logs"}}"#,
+            r#"{"jsonrpc":"2.0","id":7,"method":"resources/read","params":{"uri":"fgp://demo/health"}}"#,
+            r#"{"jsonrpc":"2.0","id":8,"method":"resources/read","params":{"uri":"fgp://other/health"}}"#,
+        ],
+    );
+
+    let capabilities = &reply(&replies, json!(0))["result"]["capabilities"];
+    assert!(capabilities["prompts"].is_object());
+    assert!(capabilities["resources"].is_object());
+
+    let prompts = reply(&replies, json!(1))["result"]["prompts"]
+        .as_array()
+        .unwrap();
+    assert_eq!(prompts.len(), 1);
+    assert_eq!(prompts[0]["name"], "demo-skill");
+    assert_eq!(prompts[0]["description"], "Demo things");
+
+    let message = &reply(&replies, json!(2))["result"]["messages"][0];
+    assert_eq!(message["role"], "user");
+    let text = message["content"]["text"].as_str().unwrap();
+    assert!(text.starts_with("Use the demo daemon."));
+    assert!(text.contains("say hi"));
+    assert_eq!(reply(&replies, json!(3))["error"]["code"], -32602);
+
+    let uris: Vec<&str> = reply(&replies, json!(4))["result"]["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["uri"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        uris,
+        [
+            "fgp://demo/health",
+            "fgp://demo/manifest",
+            "fgp://demo/logs"
+        ]
+    );
+
+    let manifest = &reply(&replies, json!(5))["result"]["contents"][0];
+    assert_eq!(manifest["mimeType"], "application/json");
+    assert_eq!(manifest["text"], r#"{"name":"demo"}"#);
+    let logs = &reply(&replies, json!(6))["result"]["contents"][0]["text"];
+    assert_eq!(logs, "started\nready");
+    let health = &reply(&replies, json!(7))["result"]["contents"][0]["text"];
+    let health: Value = serde_json::from_str(health.as_str().unwrap()).unwrap();
+    assert_eq!(health["ok"], false);
+    assert_eq!(reply(&replies, json!(8))["error"]["code"], -32002);
+}
+
+#[test]
+fn test_workflow_tools() {
+    let home = tempfile::tempdir().expect("Failed to create temp HOME");
+    let workflows = home.path().join(".fgp").join("workflows");
+    std::fs::create_dir_all(&workflows).unwrap();
+    std::fs::write(
+        workflows.join("inbox-search.yaml"),
+        r#"name: inbox-search
+description: Search the inbox
+variables:
+  query:
+    type: string
+    description: Gmail search query
+  limit: 10
+steps:
+  - service: gmail
+    method: gmail.search
+    params:
+      query: "{{query}}"
+      limit: "{{limit}}"
+"#,
+    )
+    .unwrap();
+
+    let replies = exchange_in(
+        home.path(),
+        &[
+            INITIALIZE,
+            INITIALIZED,
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"fgp_workflow__inbox-search","arguments":{"limit":5}}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"fgp_workflow__inbox-search","arguments":{"query":"x","bogus":1}}}"#,
+        ],
+    );
+
+    let tools = reply(&replies, json!(1))["result"]["tools"]
+        .as_array()
+        .unwrap();
+    let tool = tools
+        .iter()
+        .find(|t| t["name"] == "fgp_workflow__inbox-search")
+        .expect("workflow should be listed as a tool");
+    assert_eq!(tool["description"], "Search the inbox");
+    let schema = &tool["inputSchema"];
+    assert_eq!(schema["properties"]["query"]["type"], "string");
+    assert_eq!(schema["properties"]["limit"]["default"], 10);
+    assert_eq!(schema["required"], json!(["query"]));
+
+    let missing = &reply(&replies, json!(2))["error"];
+    assert_eq!(missing["code"], -32602);
+    assert!(missing["message"].as_str().unwrap().contains("query"));
+    let unknown = &reply(&replies, json!(3))["error"];
+    assert!(unknown["message"].as_str().unwrap().contains("bogus"));
+}